@@ -1,7 +1,8 @@
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
 use fast_ntt::{
+    multiply::{karatsuba, mul, toom3},
     ntt::{forward, working_modulus, Constants},
-    numbers::BigInt,
+    numbers::BigInt256,
     polynomial::{fast_mul, mul_brute, Polynomial, PolynomialFieldElement, PolynomialTrait},
 };
 use itertools::Itertools;
@@ -22,6 +23,17 @@ fn bench_mul_brute<T: PolynomialFieldElement>(x: usize, y: usize) {
     let _ = mul_brute(a, b);
 }
 
+fn bench_split<T: PolynomialFieldElement>(
+    x: usize,
+    c: &Constants<T>,
+    f: fn(Polynomial<T>, Polynomial<T>, &Constants<T>) -> Polynomial<T>,
+) {
+    let ONE = T::from(1);
+    let a = Polynomial::new(vec![0; x].iter().map(|_| ONE).collect_vec());
+    let b = Polynomial::new(vec![0; x].iter().map(|_| ONE).collect_vec());
+    let _ = f(a, b, c);
+}
+
 fn bench_forward<T: PolynomialFieldElement>(n: usize, c: &Constants<T>) {
     let ONE = T::from(1);
     let a = Polynomial::new(vec![0; n].iter().map(|_| ONE).collect_vec());
@@ -32,7 +44,7 @@ fn criterion_forward(c: &mut Criterion) {
     let mut group = c.benchmark_group("Number-Theoretic Transform Benchmarks");
     (6..deg).for_each(|n| {
         let id = BenchmarkId::new("NTT", 1 << n);
-        let c = working_modulus(BigInt::from(n), BigInt::from(2 * n + 1));
+        let c = working_modulus(BigInt256::from(n), BigInt256::from(2 * n + 1));
         group.bench_with_input(id, &n, |b, n| {
             b.iter(|| bench_forward(black_box(1 << n), black_box(&c)))
         });
@@ -44,7 +56,7 @@ fn criterion_benchmark(c: &mut Criterion) {
 
     (6..deg).for_each(|n| {
         let id = BenchmarkId::new("NTT-Based", 1 << n);
-        let N = BigInt::from((2 * n).next_power_of_two());
+        let N = BigInt256::from((2 * n).next_power_of_two());
         let M = N << 1 + 1;
         let c = working_modulus(N, M);
         group.bench_with_input(id, &n, |b, n| {
@@ -53,7 +65,22 @@ fn criterion_benchmark(c: &mut Criterion) {
 
         let id = BenchmarkId::new("Brute-Force", 1 << n);
         group.bench_with_input(id, &n, |b, n| {
-            b.iter(|| bench_mul_brute::<BigInt>(black_box(1 << n), black_box(1 << n)))
+            b.iter(|| bench_mul_brute::<BigInt256>(black_box(1 << n), black_box(1 << n)))
+        });
+
+        let id = BenchmarkId::new("Karatsuba", 1 << n);
+        group.bench_with_input(id, &n, |b, n| {
+            b.iter(|| bench_split(black_box(1 << n), black_box(&c), karatsuba))
+        });
+
+        let id = BenchmarkId::new("Toom-3", 1 << n);
+        group.bench_with_input(id, &n, |b, n| {
+            b.iter(|| bench_split(black_box(1 << n), black_box(&c), toom3))
+        });
+
+        let id = BenchmarkId::new("Hybrid", 1 << n);
+        group.bench_with_input(id, &n, |b, n| {
+            b.iter(|| bench_split(black_box(1 << n), black_box(&c), mul))
         });
     });
 }
@@ -2,7 +2,7 @@ use concrete_ntt::prime32::Plan;
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
 use fast_ntt::{
     ntt::{forward, working_modulus, Constants},
-    numbers::BigInt,
+    numbers::BigInt256,
     polynomial::{fast_mul, mul_brute, Polynomial, PolynomialFieldElement, PolynomialTrait},
 };
 use itertools::Itertools;
@@ -28,7 +28,7 @@ fn criterion_benchmark(c: &mut Criterion) {
 
     (6..deg).for_each(|n| {
         let id = BenchmarkId::new("NTT-Based", 1 << n);
-        let N = BigInt::from((2 * n).next_power_of_two());
+        let N = BigInt256::from((2 * n).next_power_of_two());
         let M = N << 1 + 1;
         let c = working_modulus(N, M);
         group.bench_with_input(id, &n, |b, n| {
@@ -41,7 +41,7 @@ fn criterion_benchmark(c: &mut Criterion) {
         let p = 1062862849;
         let plan = Plan::try_new(N, p).unwrap();
         group.bench_with_input(id, &n, |b, n| {
-            b.iter(|| bench_concrete::<BigInt>(black_box(1 << n), black_box(&plan)))
+            b.iter(|| bench_concrete::<BigInt256>(black_box(1 << n), black_box(&plan)))
         });
     });
 }
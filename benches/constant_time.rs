@@ -0,0 +1,119 @@
+// A dudect-style leakage test: each operation is timed on inputs drawn at random from two
+// classes, and Welch's t-test checks whether the two timing distributions differ. Run with
+//
+//   cargo bench --bench constant_time --features constant-time
+use std::{hint::black_box, time::Instant};
+
+use fast_ntt::{
+    ntt::{forward, working_modulus},
+    numbers::BigInt256,
+};
+use itertools::Itertools;
+use rand::{thread_rng, Rng};
+
+// |t| above this is taken as evidence of a leak, as in dudect
+const THRESHOLD: f64 = 4.5;
+
+fn welch_t(samples: &[(usize, f64)]) -> f64 {
+    // drop the slowest tenth, which is mostly interrupts and cache misses
+    let mut sorted = samples.iter().map(|&(_, t)| t).collect_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let cutoff = sorted[sorted.len() * 9 / 10];
+
+    let stats = |class: usize| {
+        let xs = samples
+            .iter()
+            .filter(|&&(c, t)| c == class && t <= cutoff)
+            .map(|&(_, t)| t)
+            .collect_vec();
+        let n = xs.len() as f64;
+        let mean = xs.iter().sum::<f64>() / n;
+        let var = xs.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / (n - 1.0);
+        (n, mean, var)
+    };
+    let (n0, m0, v0) = stats(0);
+    let (n1, m1, v1) = stats(1);
+    (m0 - m1) / (v0 / n0 + v1 / n1).sqrt()
+}
+
+fn measure<I>(
+    name: &str,
+    samples: usize,
+    mut input: impl FnMut(usize) -> I,
+    mut op: impl FnMut(I),
+) -> bool {
+    let mut rng = thread_rng();
+    let timings = (0..samples)
+        .map(|_| {
+            let class = rng.gen_range(0..2);
+            let x = input(class);
+            let start = Instant::now();
+            op(black_box(x));
+            (class, start.elapsed().as_nanos() as f64)
+        })
+        .collect_vec();
+    let t = welch_t(&timings);
+    let ok = t.abs() < THRESHOLD;
+    println!(
+        "{name:<10} t = {t:>8.2}  {}",
+        if ok { "ok" } else { "LEAK" }
+    );
+    ok
+}
+
+fn main() {
+    // `cargo test --benches` only smoke-tests the harness
+    let full = std::env::args().any(|a| a == "--bench");
+    let samples = if full { 100_000 } else { 100 };
+    let mut rng = thread_rng();
+
+    // class 0 is a fixed input that takes a shortcut in the variable-time code, class 1 is random
+    let M = BigInt256::from(998244353);
+    let rem = measure(
+        "rem",
+        samples,
+        |class| match class {
+            0 => BigInt256::from(5),
+            _ => BigInt256::from(rng.gen::<u128>()),
+        },
+        |x| {
+            black_box(x.rem(M));
+        },
+    );
+
+    let mut rng = thread_rng();
+    let base = BigInt256::from(3);
+    let mod_exp = measure(
+        "mod_exp",
+        samples / 10,
+        |class| match class {
+            0 => BigInt256::from(1),
+            _ => BigInt256::from(rng.gen::<u128>()),
+        },
+        |e| {
+            black_box(base.mod_exp(e, M));
+        },
+    );
+
+    let mut rng = thread_rng();
+    let c = working_modulus(BigInt256::from(16), BigInt256::from(1 << 10));
+    let fft = measure(
+        "forward",
+        samples / 10,
+        |class| {
+            (0..16)
+                .map(|_| match class {
+                    0 => BigInt256::from(0),
+                    _ => BigInt256::from(rng.gen::<u32>()).rem(c.N),
+                })
+                .collect_vec()
+        },
+        |v| {
+            black_box(forward(v, &c));
+        },
+    );
+
+    if full && !(rem && mod_exp && fft) {
+        std::process::exit(1);
+    }
+}
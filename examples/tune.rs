@@ -0,0 +1,7 @@
+use fast_ntt::{multiply::MulThresholds, ntt::working_modulus, numbers::BigInt256};
+
+// prints the crossovers measured on this machine, to pass to `mul_with`
+fn main() {
+    let c = working_modulus(BigInt256::from(1 << 20), BigInt256::from(952));
+    println!("{:?}", MulThresholds::tune(&c));
+}
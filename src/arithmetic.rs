@@ -0,0 +1,403 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    ops::{Div, Mul, MulAssign, Rem},
+};
+
+use crate::{
+    error::NttError,
+    gcd::div_rem,
+    multiply::{mul_poly, sqr_poly},
+    ntt::{try_working_modulus, Constants},
+    poly_le::{bits, from_le, small, to_le, trim},
+    polynomial::{Polynomial, PolynomialFieldElement, PolynomialTrait},
+};
+
+// The operators work over the integers. Products run modulo an NTT prime above every coefficient
+// of the result, so they are exact, and fail with `Overflow` when no such prime fits in `T`.
+// Division runs modulo a prime above every coefficient of the quotient and remainder, which
+// needs a monic divisor; coefficients above half the prime come back negated, wrapping like
+// `Sub` does. A `Context` keeps the primes it has chosen, or fixes one modulus for everything.
+
+// primes kept by a context before its cache starts over
+const PRIMES_CACHED: usize = 64;
+
+/// The constants behind the polynomial operators. `Context::new()` works over the integers as
+/// the operators do and caches the primes it selects, so that a run of products can share them.
+/// `Context::modulo(c)` works over `Z_N` for `N = c.N` instead, which must be prime to divide.
+#[derive(Debug, Clone)]
+pub struct Context<T: PolynomialFieldElement> {
+    modulus: Option<Constants<T>>,
+    // keyed by transform length and bits per coefficient
+    primes: RefCell<HashMap<(usize, usize), Constants<T>>>,
+}
+
+impl<T: PolynomialFieldElement> Default for Context<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: PolynomialFieldElement> Context<T> {
+    pub fn new() -> Self {
+        Self {
+            modulus: None,
+            primes: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn modulo(c: &Constants<T>) -> Self {
+        Self {
+            modulus: Some(c.clone()),
+            primes: RefCell::new(HashMap::new()),
+        }
+    }
+
+    // a prime above `2^k` with a root of unity of order `n`
+    fn prime(&self, n: usize, k: usize) -> Result<Constants<T>, NttError> {
+        let width = 64 * T::from(0).to_words().len();
+        if k + n.trailing_zeros() as usize + 2 > width / 2 {
+            return Err(NttError::Overflow);
+        }
+        let mut primes = self.primes.borrow_mut();
+        if let Some(c) = primes.get(&(n, k)) {
+            return Ok(c.clone());
+        }
+        if primes.len() >= PRIMES_CACHED {
+            primes.clear();
+        }
+        let m = (0..k).fold(T::from(1), |acc, _| acc * T::from(2));
+        let c = try_working_modulus(small(n), m)?;
+        primes.insert((n, k), c.clone());
+        Ok(c)
+    }
+
+    // the constants for a product of `a` and `b`
+    fn constants(&self, a: &Polynomial<T>, b: &Polynomial<T>) -> Result<Constants<T>, NttError> {
+        if let Some(c) = &self.modulus {
+            return Ok(c.clone());
+        }
+        let n = (a.len() + b.len()).next_power_of_two();
+        let len = a.len().min(b.len());
+        // every coefficient of the product is below `2^k`
+        let k = bits(a.max()) + bits(b.max()) + (usize::BITS - len.leading_zeros()) as usize;
+        self.prime(n, k)
+    }
+
+    pub fn mul(&self, a: &Polynomial<T>, b: &Polynomial<T>) -> Polynomial<T> {
+        self.try_mul(a, b).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// `a * b`, or `Overflow` over the integers if `T` has no prime above its coefficients.
+    pub fn try_mul(&self, a: &Polynomial<T>, b: &Polynomial<T>) -> Result<Polynomial<T>, NttError> {
+        if a.coef.is_empty() || b.coef.is_empty() {
+            return Ok(Polynomial::new(vec![]));
+        }
+        let c = self.constants(a, b)?;
+        Ok(from_le(mul_poly(
+            &to_le(a.clone(), c.N),
+            &to_le(b.clone(), c.N),
+            &c,
+        )))
+    }
+
+    // `try_mul(a, a)`, with one forward transform instead of two
+    fn try_square(&self, a: &Polynomial<T>) -> Result<Polynomial<T>, NttError> {
+        if a.coef.is_empty() {
+            return Ok(Polynomial::new(vec![]));
+        }
+        let c = self.constants(a, a)?;
+        Ok(from_le(sqr_poly(&to_le(a.clone(), c.N), &c)))
+    }
+
+    pub fn pow(&self, a: &Polynomial<T>, k: u64) -> Polynomial<T> {
+        self.try_pow(a, k).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// `a^k` by repeated squaring, where each squaring takes a single forward transform.
+    pub fn try_pow(&self, a: &Polynomial<T>, mut k: u64) -> Result<Polynomial<T>, NttError> {
+        let mut out = Polynomial::new(vec![T::from(1)]);
+        let mut base = a.clone();
+        while k > 0 {
+            if k & 1 == 1 {
+                out = self.try_mul(&out, &base)?;
+            }
+            k >>= 1;
+            if k > 0 {
+                base = self.try_square(&base)?;
+            }
+        }
+        Ok(out)
+    }
+
+    /// `s a`, reduced modulo `N` under `Context::modulo`.
+    pub fn scale(&self, a: &Polynomial<T>, s: T) -> Polynomial<T> {
+        let coef = match &self.modulus {
+            Some(c) => {
+                let s = s.rem(c.N);
+                a.coef.iter().map(|&x| (x.rem(c.N) * s).rem(c.N)).collect()
+            }
+            None => a.coef.iter().map(|&x| x * s).collect(),
+        };
+        Polynomial::new(coef)
+    }
+
+    /// Quotient and remainder of `a` by `b`. Over the integers `b` must be monic, or this fails
+    /// with `NotInvertible`.
+    pub fn try_div_rem(
+        &self,
+        a: &Polynomial<T>,
+        b: &Polynomial<T>,
+    ) -> Result<(Polynomial<T>, Polynomial<T>), NttError> {
+        if let Some(c) = &self.modulus {
+            return div_rem(a.clone(), b.clone(), c.N);
+        }
+        let b_le = trim(b.coef.clone());
+        let Some(&lead) = b_le.last() else {
+            return Err(NttError::DivisionByZero);
+        };
+        if lead != T::from(1) {
+            return Err(NttError::NotInvertible);
+        }
+        // with `A` and `B` the largest coefficients of `a` and `b`, those of the quotient and
+        // remainder stay below `A (B + 1)^(deg a - deg b + 1)` in absolute value
+        let steps = (a.len() + 1).saturating_sub(b_le.len());
+        let k = bits(a.max()) + steps * bits(b.max() + T::from(1)) + 1;
+        let c = self.prime((a.len() + b_le.len()).next_power_of_two(), k)?;
+        let (q, r) = div_rem(a.clone(), b.clone(), c.N)?;
+        Ok((signed(q, c.N), signed(r, c.N)))
+    }
+}
+
+// lifts coefficients above `p / 2` to their negatives, under the default modulus
+fn signed<T: PolynomialFieldElement>(a: Polynomial<T>, p: T) -> Polynomial<T> {
+    let mut half = p;
+    half >>= 1;
+    Polynomial::new(
+        a.coef
+            .iter()
+            .map(|&x| if x > half { -(p - x) } else { x })
+            .collect(),
+    )
+}
+
+impl<T: PolynomialFieldElement> Polynomial<T> {
+    /// `self^k` over the integers, as `Context::pow` computes it.
+    pub fn pow(&self, k: u64) -> Self {
+        Context::new().pow(self, k)
+    }
+
+    /// Quotient and remainder by `rhs` over the integers, as `/` and `%` compute them, but
+    /// without panicking.
+    pub fn try_div_rem(&self, rhs: &Self) -> Result<(Self, Self), NttError> {
+        Context::new().try_div_rem(self, rhs)
+    }
+
+    pub fn try_div(&self, rhs: &Self) -> Result<Self, NttError> {
+        Ok(self.try_div_rem(rhs)?.0)
+    }
+
+    pub fn try_rem(&self, rhs: &Self) -> Result<Self, NttError> {
+        Ok(self.try_div_rem(rhs)?.1)
+    }
+}
+
+fn quotient<T: PolynomialFieldElement>(
+    a: &Polynomial<T>,
+    b: &Polynomial<T>,
+) -> (Polynomial<T>, Polynomial<T>) {
+    a.try_div_rem(b).unwrap_or_else(|e| panic!("{}", e))
+}
+
+impl<T: PolynomialFieldElement> Mul<&Polynomial<T>> for &Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn mul(self, rhs: &Polynomial<T>) -> Self::Output {
+        Context::new().mul(self, rhs)
+    }
+}
+
+impl<T: PolynomialFieldElement> Mul<Polynomial<T>> for Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn mul(self, rhs: Polynomial<T>) -> Self::Output {
+        Context::new().mul(&self, &rhs)
+    }
+}
+
+impl<T: PolynomialFieldElement> Mul<T> for &Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        Context::new().scale(self, rhs)
+    }
+}
+
+impl<T: PolynomialFieldElement> Mul<T> for Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        Context::new().scale(&self, rhs)
+    }
+}
+
+impl<T: PolynomialFieldElement> MulAssign<&Polynomial<T>> for Polynomial<T> {
+    fn mul_assign(&mut self, rhs: &Polynomial<T>) {
+        *self = Context::new().mul(self, rhs);
+    }
+}
+
+impl<T: PolynomialFieldElement> MulAssign<Polynomial<T>> for Polynomial<T> {
+    fn mul_assign(&mut self, rhs: Polynomial<T>) {
+        *self = Context::new().mul(self, &rhs);
+    }
+}
+
+impl<T: PolynomialFieldElement> MulAssign<T> for Polynomial<T> {
+    fn mul_assign(&mut self, rhs: T) {
+        *self = Context::new().scale(self, rhs);
+    }
+}
+
+impl<T: PolynomialFieldElement> Div<&Polynomial<T>> for &Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn div(self, rhs: &Polynomial<T>) -> Self::Output {
+        quotient(self, rhs).0
+    }
+}
+
+impl<T: PolynomialFieldElement> Div<Polynomial<T>> for Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn div(self, rhs: Polynomial<T>) -> Self::Output {
+        quotient(&self, &rhs).0
+    }
+}
+
+impl<T: PolynomialFieldElement> Rem<&Polynomial<T>> for &Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn rem(self, rhs: &Polynomial<T>) -> Self::Output {
+        quotient(self, rhs).1
+    }
+}
+
+impl<T: PolynomialFieldElement> Rem<Polynomial<T>> for Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn rem(self, rhs: Polynomial<T>) -> Self::Output {
+        quotient(&self, &rhs).1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::{
+        arithmetic::{Context, PRIMES_CACHED},
+        error::NttError,
+        numbers::BigInt256,
+        polynomial::{mul_brute, Polynomial},
+        test_utils::{constants, poly, random_monic, random_poly, reduced, P},
+    };
+
+    #[test]
+    fn test_exact() {
+        // (x + 1)(x + 2) = x^2 + 3x + 2
+        let a = poly(&[1, 1]);
+        let b = poly(&[2, 1]);
+        assert_eq!(&a * &b, poly(&[2, 3, 1]));
+        assert_eq!(a.pow(6), poly(&[1, 6, 15, 20, 15, 6, 1]));
+        assert_eq!(a.pow(0), poly(&[1]));
+        assert_eq!(&a * &poly(&[]), poly(&[]));
+        assert_eq!(&a * BigInt256::from(3), poly(&[3, 3]));
+
+        // large enough for the transforms, with exact coefficients up to 2^88
+        let mut rng = StdRng::seed_from_u64(1);
+        let a = random_poly(300, 1 << 40, &mut rng);
+        let b = random_poly(200, 1 << 40, &mut rng);
+        let expected = mul_brute(a.clone(), b.clone());
+        assert_eq!(a.clone() * b.clone(), expected);
+        let mut c = a.clone();
+        c *= &b;
+        assert_eq!(c, expected);
+        assert_eq!(a.pow(2), mul_brute(a.clone(), a));
+
+        // a wrapped coefficient is too wide for any prime, so the product fails
+        let a = Polynomial::new(vec![-BigInt256::from(1), BigInt256::from(1)]);
+        let b = poly(&[1, 1]);
+        assert_eq!(Context::new().try_mul(&a, &b), Err(NttError::Overflow));
+        assert_eq!(Context::new().try_pow(&a, 2), Err(NttError::Overflow));
+    }
+
+    #[test]
+    fn test_division() {
+        // x^2 + 3x + 2 = (x + 2)(x + 1), and x^2 = (x - 1)(x + 1) + 1
+        let b = poly(&[1, 1]);
+        assert_eq!(&poly(&[2, 3, 1]) / &b, poly(&[2, 1]));
+        assert_eq!(&poly(&[2, 3, 1]) % &b, poly(&[]));
+        assert_eq!(
+            poly(&[0, 0, 1]).try_div_rem(&b),
+            Ok((
+                Polynomial::new(vec![-BigInt256::from(1), BigInt256::from(1)]),
+                poly(&[1])
+            ))
+        );
+        assert_eq!(poly(&[1]) % poly(&[0, 1]), poly(&[1]));
+
+        let mut rng = StdRng::seed_from_u64(3);
+        let a = random_poly(12, 16, &mut rng);
+        let b = Polynomial::new(random_monic(6, 16, &mut rng));
+        let r = random_poly(5, 16, &mut rng);
+        let n = (&a * &b) + r.clone();
+        assert_eq!(n.try_div_rem(&b), Ok((a, r)));
+
+        assert_eq!(b.try_div(&poly(&[1, 2])), Err(NttError::NotInvertible));
+        assert_eq!(b.try_rem(&poly(&[])), Err(NttError::DivisionByZero));
+    }
+
+    #[test]
+    fn test_context() {
+        let c = constants();
+        let ctx = Context::modulo(&c);
+        let mut rng = StdRng::seed_from_u64(2);
+        let a = random_poly(300, P, &mut rng);
+        let b = random_poly(120, P, &mut rng);
+        let r = random_poly(119, P, &mut rng);
+
+        let ab = ctx.mul(&a, &b);
+        assert_eq!(ab, reduced(mul_brute(a.clone(), b.clone()), c.N));
+        let neg = a.coef.iter().map(|&x| BigInt256::from(P) - x).collect();
+        assert_eq!(ctx.scale(&a, BigInt256::from(P - 1)), Polynomial::new(neg));
+
+        // the squarings agree with plain products
+        let a3 = ctx.mul(&ctx.mul(&a, &a), &a);
+        assert_eq!(ctx.pow(&a, 3), a3);
+        assert_eq!(ctx.pow(&a, 4), ctx.mul(&a3, &a));
+
+        // `b` is not monic, which only matters over the integers
+        let n = reduced(ab + r.clone(), c.N);
+        assert_eq!(ctx.try_div_rem(&n, &b), Ok((a.clone(), r.clone())));
+        assert_eq!(
+            ctx.try_div_rem(&n, &poly(&[])),
+            Err(NttError::DivisionByZero)
+        );
+    }
+
+    #[test]
+    fn test_prime_cache() {
+        // products with coefficients of many distinct sizes each want their own prime
+        let ctx = Context::new();
+        let b = poly(&[3, 1, 4]);
+        for (len, k) in [2, 6]
+            .into_iter()
+            .flat_map(|len| (0..PRIMES_CACHED).map(move |k| (len, k)))
+        {
+            let a = Polynomial::new(vec![BigInt256::from(1_u128 << k); len]);
+            assert_eq!(ctx.mul(&a, &b), mul_brute(a.clone(), b.clone()));
+        }
+        assert!(ctx.primes.borrow().len() <= PRIMES_CACHED);
+    }
+}
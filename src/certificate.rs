@@ -0,0 +1,322 @@
+use crate::{
+    poly_le::bits,
+    polynomial::PolynomialFieldElement,
+    prime::{factor, gcd, group_factors, is_prime, is_primitive_root, trial_divide},
+};
+
+// primes below this bound are certified by trial division alone
+const SMALL_PRIME_BOUND: u32 = 1 << 20;
+const MAX_WITNESS: u32 = 1 << 12;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CertifiedFactor<T: PolynomialFieldElement> {
+    pub prime: T,
+    pub exponent: usize,
+    pub certificate: Certificate<T>,
+}
+
+/// A proof that `p` is prime. Every prime factor used in the proof carries its own certificate.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Certificate<T: PolynomialFieldElement> {
+    /// `p < 2^20`, checked by trial division
+    Small(T),
+    /// Lucas-Pratt: `generator` has order `p - 1`, and `factors` is the full factorization of `p - 1`
+    Pratt {
+        p: T,
+        generator: T,
+        factors: Vec<CertifiedFactor<T>>,
+    },
+    /// Pocklington: `factors` multiply to some `F | p - 1` with `F^2 > p`, and `witnesses[i]`
+    /// proves that every prime factor of `p` is `1 (mod factors[i].prime)`
+    Pocklington {
+        p: T,
+        factors: Vec<CertifiedFactor<T>>,
+        witnesses: Vec<T>,
+    },
+}
+
+impl<T: PolynomialFieldElement> Certificate<T> {
+    pub fn prime(&self) -> T {
+        match self {
+            Certificate::Small(p) => *p,
+            Certificate::Pratt { p, .. } => *p,
+            Certificate::Pocklington { p, .. } => *p,
+        }
+    }
+}
+
+fn is_small_prime<T: PolynomialFieldElement>(p: T) -> bool {
+    if p < T::from(2) || p >= T::from(SMALL_PRIME_BOUND) {
+        return false;
+    }
+    let mut d = 2;
+    while T::from(d * d) <= p {
+        if p.rem(T::from(d)).is_zero() {
+            return false;
+        }
+        d += 1;
+    }
+    true
+}
+
+fn pow<T: PolynomialFieldElement>(q: T, e: usize) -> T {
+    (0..e).fold(T::from(1), |acc, _| acc * q)
+}
+
+fn certify_factors<T: PolynomialFieldElement>(
+    factors: Vec<(T, usize)>,
+    certify: fn(T) -> Option<Certificate<T>>,
+) -> Option<Vec<CertifiedFactor<T>>> {
+    factors
+        .into_iter()
+        .map(|(prime, exponent)| {
+            let certificate = if prime < T::from(SMALL_PRIME_BOUND) {
+                Certificate::Small(prime)
+            } else {
+                certify(prime)?
+            };
+            Some(CertifiedFactor {
+                prime,
+                exponent,
+                certificate,
+            })
+        })
+        .collect()
+}
+
+// `a^(p - 1) = 1` and `gcd(a^((p - 1) / q) - 1, p) = 1`
+fn is_pocklington_witness<T: PolynomialFieldElement>(a: T, p: T, q: T) -> bool {
+    let one = T::from(1);
+    let totient = p - one;
+    if a.mod_exp(totient, p) != one {
+        return false;
+    }
+    let x = a.mod_exp(totient / q, p);
+    gcd(p, x - one) == one
+}
+
+/// Builds a Lucas-Pratt certificate for `p`, or `None` if `p` is not prime.
+pub fn pratt_certificate<T: PolynomialFieldElement>(p: T) -> Option<Certificate<T>> {
+    if p < T::from(SMALL_PRIME_BOUND) {
+        return is_small_prime(p).then_some(Certificate::Small(p));
+    }
+    if !is_prime(p) {
+        return None;
+    }
+    let one = T::from(1);
+    let factors = factor(p - one);
+    let mut generator = T::from(2);
+    while !is_primitive_root(generator, p, &factors) {
+        generator += one;
+    }
+    Some(Certificate::Pratt {
+        p,
+        generator,
+        factors: certify_factors(factors, pratt_certificate)?,
+    })
+}
+
+/// Builds a Pocklington certificate for `p`, or `None` if `p` is not prime. Only enough of
+/// `p - 1` to exceed `sqrt(p)` is factored, so primes with a smooth `p - 1` (such as the
+/// moduli returned by `working_modulus`) are cheap to certify.
+pub fn pocklington_certificate<T: PolynomialFieldElement>(p: T) -> Option<Certificate<T>> {
+    if p < T::from(SMALL_PRIME_BOUND) {
+        return is_small_prime(p).then_some(Certificate::Small(p));
+    }
+    if !is_prime(p) {
+        return None;
+    }
+    let one = T::from(1);
+    let (mut primes, cofactor) = trial_divide(p - one);
+    let f = primes.iter().fold(one, |acc, &q| acc * q);
+    if f <= p / f {
+        primes.extend(factor(cofactor).into_iter().flat_map(|(q, e)| vec![q; e]));
+    }
+
+    // keep the largest primes until their product exceeds `sqrt(p)`
+    let mut factors = group_factors(primes);
+    factors.reverse();
+    let mut f = one;
+    let mut needed = 0;
+    while f <= p / f {
+        let (q, e) = factors[needed];
+        f *= pow(q, e);
+        needed += 1;
+    }
+    factors.truncate(needed);
+
+    let witnesses = factors
+        .iter()
+        .map(|&(q, _)| {
+            (2..MAX_WITNESS)
+                .map(T::from)
+                .find(|&a| is_pocklington_witness(a, p, q))
+        })
+        .collect::<Option<Vec<T>>>()?;
+    Some(Certificate::Pocklington {
+        p,
+        factors: certify_factors(factors, pocklington_certificate)?,
+        witnesses,
+    })
+}
+
+// Divides `n` by each certified `q^e` in turn and returns what is left, or `None` if a factor
+// is not proven prime or does not divide. Multiplying the factors instead could wrap at the
+// default modulus and let a forged list pass for `n`.
+fn divide_out<T: PolynomialFieldElement>(n: T, factors: &[CertifiedFactor<T>]) -> Option<T> {
+    let limit = bits(n);
+    factors.iter().try_fold(n, |rest, f| {
+        let valid = f.exponent > 0
+            && f.exponent <= limit
+            && f.certificate.prime() == f.prime
+            && verify_certificate(&f.certificate);
+        if !valid {
+            return None;
+        }
+        (0..f.exponent).try_fold(rest, |r, _| r.rem(f.prime).is_zero().then(|| r / f.prime))
+    })
+}
+
+/// Checks that `cert` is a valid proof of primality for `cert.prime()`.
+pub fn verify_certificate<T: PolynomialFieldElement>(cert: &Certificate<T>) -> bool {
+    let one = T::from(1);
+    match cert {
+        Certificate::Small(p) => is_small_prime(*p),
+        Certificate::Pratt {
+            p,
+            generator,
+            factors,
+        } => {
+            let p = *p;
+            // the factors must be all of `p - 1`, or the order of `generator` is not pinned down
+            if p <= one || p.is_even() || divide_out(p - one, factors) != Some(one) {
+                return false;
+            }
+            let factors = factors
+                .iter()
+                .map(|f| (f.prime, f.exponent))
+                .collect::<Vec<_>>();
+            generator.mod_exp(p - one, p) == one && is_primitive_root(*generator, p, &factors)
+        }
+        Certificate::Pocklington {
+            p,
+            factors,
+            witnesses,
+        } => {
+            let p = *p;
+            if p <= one || p.is_even() || factors.len() != witnesses.len() {
+                return false;
+            }
+            let f = match divide_out(p - one, factors) {
+                Some(rest) => (p - one) / rest,
+                None => return false,
+            };
+            f > p / f
+                && factors
+                    .iter()
+                    .zip(witnesses)
+                    .all(|(f, &a)| is_pocklington_witness(a, p, f.prime))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        certificate::{
+            pocklington_certificate, pratt_certificate, verify_certificate, Certificate,
+            CertifiedFactor,
+        },
+        ntt::working_modulus,
+        numbers::{BigInt256, BigInt64},
+    };
+
+    #[test]
+    fn test_pratt_certificate() {
+        let p = BigInt256::from((1_u128 << 61) - 1);
+        let cert = pratt_certificate(p).unwrap();
+        assert_eq!(cert.prime(), p);
+        assert!(verify_certificate(&cert));
+
+        assert!(pratt_certificate(BigInt256::from(3215031751_u64)).is_none());
+        assert!(verify_certificate(
+            &pratt_certificate(BigInt256::from(97)).unwrap()
+        ));
+    }
+
+    #[test]
+    fn test_pocklington_certificate() {
+        let c = working_modulus(BigInt256::from(1 << 16), BigInt256::from(1_u128 << 100));
+        let cert = c.certificate().unwrap();
+        assert_eq!(cert.prime(), c.N);
+        assert!(verify_certificate(&cert));
+
+        let p = BigInt256::from((1_u128 << 89) - 1);
+        let cert = pocklington_certificate(p).unwrap();
+        assert!(verify_certificate(&cert));
+    }
+
+    #[test]
+    fn test_forged_certificate() {
+        let one = BigInt256::from(1);
+        let p = BigInt256::from((1_u128 << 61) - 1);
+        let cert = match pratt_certificate(p).unwrap() {
+            Certificate::Pratt { factors, .. } => Certificate::Pratt {
+                p,
+                generator: one,
+                factors,
+            },
+            _ => unreachable!(),
+        };
+        assert!(!verify_certificate(&cert));
+
+        let n = BigInt256::from(3215031751_u64);
+        let cert = match pocklington_certificate(BigInt256::from(1_000_000_007)).unwrap() {
+            Certificate::Pocklington {
+                factors, witnesses, ..
+            } => Certificate::Pocklington {
+                p: n,
+                factors,
+                witnesses,
+            },
+            _ => unreachable!(),
+        };
+        assert!(!verify_certificate(&cert));
+
+        // 2^32 + 1 = 641 * 6700417 passes the Fermat test to base 2, and these small primes
+        // multiply to 2^32 + 183 (2^64 - 1), which wraps to `p - 1` under the default modulus of
+        // `BigInt64`
+        let p = BigInt64::try_from((1_u64 << 32) + 1).unwrap();
+        let factors = [101_u32, 431, 8627, 16087, 19373, 28843]
+            .map(|q| CertifiedFactor {
+                prime: BigInt64::from(q),
+                exponent: 1,
+                certificate: Certificate::Small(BigInt64::from(q)),
+            })
+            .to_vec();
+        let forged = Certificate::Pratt {
+            p,
+            generator: BigInt64::from(2),
+            factors,
+        };
+        assert!(!verify_certificate(&forged));
+
+        // nor may an exponent run the verifier for ever
+        let cert = match pratt_certificate(BigInt64::from(1_000_000_007)).unwrap() {
+            Certificate::Pratt {
+                p,
+                generator,
+                mut factors,
+            } => {
+                factors[0].exponent = usize::MAX;
+                Certificate::Pratt {
+                    p,
+                    generator,
+                    factors,
+                }
+            }
+            _ => unreachable!(),
+        };
+        assert!(!verify_certificate(&cert));
+    }
+}
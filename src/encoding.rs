@@ -0,0 +1,229 @@
+use crate::{
+    error::NttError,
+    poly_le::bits,
+    polynomial::{Polynomial, PolynomialFieldElement},
+};
+
+// Layout of `Polynomial::to_bytes`, all integers little-endian:
+//
+//   u8          byte length `m` of the modulus
+//   [u8; m]     modulus `q`
+//   u32         number of coefficients `n`
+//   u16         coefficient width `d = bits(q - 1)`
+//   [u8]        `ceil(n * d / 8)` bytes of coefficients, constant term first, packed
+//               least-significant bit first with zero padding in the final byte
+
+pub(crate) fn from_words<T: PolynomialFieldElement>(words: &[u64]) -> T {
+    // words go in 32 bits at a time, since neither `2^64` nor `From<u64>` exists at 64 bits
+    let half = T::from(1_u32 << 16) * T::from(1_u32 << 16);
+    words.iter().rev().fold(T::from(0), |acc, &w| {
+        (acc * half + T::from((w >> 32) as u32)) * half + T::from(w as u32)
+    })
+}
+
+/// Packs each coefficient into `d` bits, least-significant bit first, as in the
+/// `ByteEncode` routines of lattice specifications.
+pub fn pack<T: PolynomialFieldElement>(coef: &[T], d: usize) -> Result<Vec<u8>, NttError> {
+    let mut out = vec![0_u8; (coef.len() * d).div_ceil(8)];
+    for (i, &x) in coef.iter().enumerate() {
+        if bits(x) > d {
+            return Err(NttError::Overflow);
+        }
+        let words = x.to_words();
+        (0..d).for_each(|j| {
+            let bit = words.get(j / 64).map_or(0, |w| (w >> (j % 64)) & 1);
+            let k = i * d + j;
+            out[k / 8] |= (bit as u8) << (k % 8);
+        });
+    }
+    Ok(out)
+}
+
+/// Inverse of `pack`. `bytes` must hold exactly `n` coefficients with zero padding bits.
+pub fn unpack<T: PolynomialFieldElement>(
+    bytes: &[u8],
+    d: usize,
+    n: usize,
+) -> Result<Vec<T>, NttError> {
+    if bytes.len() != (n * d).div_ceil(8) || d > 64 * T::from(0).to_words().len() {
+        return Err(NttError::InvalidEncoding);
+    }
+    if (n * d..8 * bytes.len()).any(|k| bit(bytes, k) != 0) {
+        return Err(NttError::InvalidEncoding);
+    }
+    Ok((0..n).map(|i| from_words(&chunk(bytes, d, i))).collect())
+}
+
+fn bit(bytes: &[u8], k: usize) -> u64 {
+    ((bytes[k / 8] >> (k % 8)) & 1) as u64
+}
+
+// the little-endian words of the `i`-th `d`-bit chunk of `bytes`
+pub(crate) fn chunk(bytes: &[u8], d: usize, i: usize) -> Vec<u64> {
+    let mut words = vec![0_u64; d.div_ceil(64)];
+    (0..d).for_each(|j| words[j / 64] |= bit(bytes, i * d + j) << (j % 64));
+    words
+}
+
+fn take<'a>(bytes: &mut &'a [u8], n: usize) -> Result<&'a [u8], NttError> {
+    if bytes.len() < n {
+        return Err(NttError::InvalidEncoding);
+    }
+    let (head, tail) = bytes.split_at(n);
+    *bytes = tail;
+    Ok(head)
+}
+
+impl<T: PolynomialFieldElement> Polynomial<T> {
+    /// Encodes the polynomial over `Z_q` in the canonical format above. Every coefficient
+    /// must already be reduced below `q`.
+    pub fn to_bytes(&self, q: T) -> Result<Vec<u8>, NttError> {
+        let one = T::from(1);
+        if q <= one {
+            return Err(NttError::InvalidEncoding);
+        }
+        if self.coef.iter().any(|&x| x >= q) {
+            return Err(NttError::Overflow);
+        }
+        if bits(q) > 8 * u8::MAX as usize {
+            return Err(NttError::Overflow);
+        }
+        let n = u32::try_from(self.coef.len()).map_err(|_| NttError::Overflow)?;
+        let d = bits(q - one);
+
+        let modulus: Vec<u8> = q
+            .to_words()
+            .iter()
+            .flat_map(|w| w.to_le_bytes())
+            .take(bits(q).div_ceil(8))
+            .collect();
+        let mut out = vec![modulus.len() as u8];
+        out.extend(modulus);
+        out.extend(n.to_le_bytes());
+        out.extend((d as u16).to_le_bytes());
+        out.extend(pack(&self.coef, d)?);
+        Ok(out)
+    }
+
+    /// Decodes a polynomial written by `to_bytes`, returning it along with its modulus.
+    /// Non-canonical encodings and out-of-range coefficients are rejected.
+    pub fn from_bytes(bytes: &[u8]) -> Result<(Self, T), NttError> {
+        let one = T::from(1);
+        let mut rest = bytes;
+        let m = take(&mut rest, 1)?[0] as usize;
+        if m == 0 {
+            return Err(NttError::InvalidEncoding);
+        }
+        let modulus = take(&mut rest, m)?;
+        if modulus[m - 1] == 0 {
+            return Err(NttError::InvalidEncoding);
+        }
+        let mut words = vec![0_u64; m.div_ceil(8)];
+        modulus
+            .iter()
+            .enumerate()
+            .for_each(|(i, &b)| words[i / 8] |= (b as u64) << (8 * (i % 8)));
+        let q: T = from_words(&words);
+        if q <= one {
+            return Err(NttError::InvalidEncoding);
+        }
+
+        let n = u32::from_le_bytes(take(&mut rest, 4)?.try_into().unwrap()) as usize;
+        let d = u16::from_le_bytes(take(&mut rest, 2)?.try_into().unwrap()) as usize;
+        if d != bits(q - one) {
+            return Err(NttError::InvalidEncoding);
+        }
+
+        let coef: Vec<T> = unpack(rest, d, n)?;
+        if coef.iter().any(|&x| x >= q) {
+            return Err(NttError::Overflow);
+        }
+        // `to_bytes` never writes a zero leading coefficient, as polynomials are trimmed
+        if coef.last().is_some_and(|x| x.is_zero()) {
+            return Err(NttError::InvalidEncoding);
+        }
+        Ok((Polynomial::new(coef), q))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::{
+        encoding::{pack, unpack},
+        error::NttError,
+        numbers::BigInt256,
+        polynomial::Polynomial,
+    };
+
+    #[test]
+    fn test_pack() {
+        let coef = [0xabc_u32, 0x123].map(BigInt256::from);
+        let bytes = pack(&coef, 12).unwrap();
+        assert_eq!(bytes, vec![0xbc, 0x3a, 0x12]);
+        assert_eq!(unpack::<BigInt256>(&bytes, 12, 2).unwrap(), coef.to_vec());
+        assert_eq!(pack(&coef, 11).unwrap_err(), NttError::Overflow);
+
+        let coef = [(1_u128 << 100) + 7, 3].map(BigInt256::from);
+        let bytes = pack(&coef, 101).unwrap();
+        assert_eq!(bytes.len(), 26);
+        assert_eq!(unpack::<BigInt256>(&bytes, 101, 2).unwrap(), coef.to_vec());
+        assert_eq!(
+            unpack::<BigInt256>(&[0xff, 0xff], 12, 1).unwrap_err(),
+            NttError::InvalidEncoding
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let q = BigInt256::from(3329);
+        let a = Polynomial::new(
+            (0..256)
+                .map(|x| BigInt256::from(x * 13 % 3329))
+                .collect_vec(),
+        );
+        let bytes = a.to_bytes(q).unwrap();
+        assert_eq!(bytes.len(), 1 + 2 + 4 + 2 + 384);
+        assert_eq!(&bytes[..9], &[2, 0x01, 0x0d, 0, 1, 0, 0, 12, 0]);
+
+        let (b, p) = Polynomial::<BigInt256>::from_bytes(&bytes).unwrap();
+        assert_eq!(a.coef, b.coef);
+        assert_eq!(p, q);
+    }
+
+    #[test]
+    fn test_validation() {
+        let q = BigInt256::from(3329);
+        let a = Polynomial::new(vec![BigInt256::from(3329)]);
+        assert_eq!(a.to_bytes(q).unwrap_err(), NttError::Overflow);
+
+        let a = Polynomial::new(vec![BigInt256::from(1), BigInt256::from(2)]);
+        let bytes = a.to_bytes(q).unwrap();
+        let decode = |b: &[u8]| Polynomial::<BigInt256>::from_bytes(b).map(|(p, _)| p.coef);
+        assert_eq!(
+            decode(&bytes[..bytes.len() - 1]),
+            Err(NttError::InvalidEncoding)
+        );
+        assert_eq!(
+            decode(&[bytes.as_slice(), &[0]].concat()),
+            Err(NttError::InvalidEncoding)
+        );
+
+        // a coefficient of 4095 fits in 12 bits but is not below 3329
+        let mut bad = bytes.clone();
+        bad[9] = 0xff;
+        bad[10] |= 0x0f;
+        assert_eq!(decode(&bad), Err(NttError::Overflow));
+
+        // nor may the leading coefficient be zero
+        let mut bad = bytes.clone();
+        bad[10] = 0;
+        assert_eq!(decode(&bad), Err(NttError::InvalidEncoding));
+
+        // the width must match the modulus
+        let mut bad = bytes;
+        bad[7] = 13;
+        assert_eq!(decode(&bad), Err(NttError::InvalidEncoding));
+    }
+}
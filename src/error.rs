@@ -0,0 +1,54 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NttError {
+    NonPowerOfTwoLength(usize),
+    EvenModulus,
+    NoRootOfUnity,
+    NoPrime,
+    ZeroPolynomial,
+    DivisionByZero,
+    Overflow,
+    EmptyString,
+    InvalidDigit(char),
+    InvalidRadix(u32),
+    InvalidEncoding,
+    NotInvertible,
+    TooFewTerms(usize),
+    LengthMismatch(usize, usize),
+    InvalidSigma,
+}
+
+impl Display for NttError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NttError::NonPowerOfTwoLength(n) => {
+                write!(f, "transform length {} is not a power of two", n)
+            }
+            NttError::EvenModulus => write!(f, "modulus must be odd"),
+            NttError::NoRootOfUnity => {
+                write!(f, "modulus has no root of unity of the requested order")
+            }
+            NttError::NoPrime => write!(f, "no prime satisfies the requested constraints"),
+            NttError::ZeroPolynomial => write!(f, "operation is undefined for the zero polynomial"),
+            NttError::DivisionByZero => write!(f, "division by zero"),
+            NttError::Overflow => write!(f, "value exceeds the size limits of the target type"),
+            NttError::EmptyString => write!(f, "cannot parse integer from empty string"),
+            NttError::InvalidDigit(c) => write!(f, "invalid digit {:?} found in string", c),
+            NttError::InvalidRadix(r) => write!(f, "radix {} is not in the range 2..=36", r),
+            NttError::InvalidEncoding => write!(f, "malformed or non-canonical encoding"),
+            NttError::NotInvertible => write!(f, "element has no inverse modulo the given modulus"),
+            NttError::TooFewTerms(n) => write!(f, "at least {} initial terms are required", n),
+            NttError::LengthMismatch(a, b) => {
+                write!(
+                    f,
+                    "operand lengths {} and {} do not suit the operation",
+                    a, b
+                )
+            }
+            NttError::InvalidSigma => write!(f, "Gaussian width must be positive and finite"),
+        }
+    }
+}
+
+impl std::error::Error for NttError {}
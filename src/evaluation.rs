@@ -0,0 +1,227 @@
+use crate::{
+    multiply::mul_poly,
+    ntt::Constants,
+    poly_le::{add_poly, from_le, inv_mod, small, to_default, to_le, trim, zero_mod},
+    polynomial::{Polynomial, PolynomialFieldElement},
+};
+
+impl<T: PolynomialFieldElement> Polynomial<T> {
+    /// The value at `x` by Horner's rule, in the arithmetic of `T`, so it wraps like `Add` and
+    /// `Mul` do.
+    pub fn evaluate(&self, x: T) -> T {
+        self.coef
+            .iter()
+            .rev()
+            .fold(T::from(0), |acc, &a| acc * x + a)
+    }
+
+    /// The value at `x` over `Z_q`, for polynomials whose value would wrap in `T`.
+    pub fn evaluate_mod(&self, x: T, q: T) -> T {
+        let zero = zero_mod(q);
+        let x = zero + x.rem(q);
+        let acc = self
+            .coef
+            .iter()
+            .rev()
+            .fold(zero, |acc, &a| (acc * x + a.rem(q)).rem(q));
+        to_default(acc)
+    }
+
+    /// `f(x + a)` over `Z_p`, with `p = c.N`, from one convolution of `i! f_i` with `a^j / j!`.
+    /// Falls back to the quadratic method when `deg f >= p`, where the factorials vanish.
+    pub fn taylor_shift(&self, a: T, c: &Constants<T>) -> Self {
+        let p = c.N;
+        let f = to_le(self.clone(), p);
+        let n = f.len();
+        let zero = zero_mod(p);
+        let a = zero + a.rem(p);
+        if n < 2 || a.is_zero() {
+            return from_le(f);
+        }
+        if small::<T>(n - 1) >= p {
+            let mut f = f;
+            (0..n - 1).for_each(|i| {
+                (i..n - 1)
+                    .rev()
+                    .for_each(|j| f[j] = (f[j] + a * f[j + 1]).rem(p))
+            });
+            return from_le(trim(f));
+        }
+
+        let mut fact = vec![zero + T::from(1); n];
+        (1..n).for_each(|i| fact[i] = (fact[i - 1] * small(i)).rem(p));
+        let mut inv_fact = vec![inv_mod(fact[n - 1], p); n];
+        (1..n)
+            .rev()
+            .for_each(|i| inv_fact[i - 1] = (inv_fact[i] * small(i)).rem(p));
+
+        let u = (0..n).map(|i| (f[i] * fact[i]).rem(p)).collect::<Vec<_>>();
+        // `a^j / j!` stored reversed, so coefficient `n - 1 + k` of the product is the sum over
+        // `i` of `u_i a^(i - k) / (i - k)!`
+        let mut v = vec![zero; n];
+        let mut pow = zero + T::from(1);
+        for j in 0..n {
+            v[n - 1 - j] = (pow * inv_fact[j]).rem(p);
+            pow = (pow * a).rem(p);
+        }
+        let mut w = mul_poly(&trim(u), &v, c);
+        w.resize(2 * n - 1, zero);
+        let out = (0..n)
+            .map(|k| (w[n - 1 + k] * inv_fact[k]).rem(p))
+            .collect::<Vec<_>>();
+        from_le(trim(out))
+    }
+}
+
+fn truncate<T: PolynomialFieldElement>(mut a: Vec<T>, n: usize) -> Vec<T> {
+    a.truncate(n);
+    trim(a)
+}
+
+/// The power series `f(g(x)) mod x^n` over `Z_p`, with `p = c.N`, by Brent-Kung: `f` is split
+/// into `k ~ sqrt(deg f)` blocks, evaluated at `g` from `g, ..., g^k`, and combined by Horner's
+/// rule in `g^k`. This takes `O(sqrt(deg f))` products and `O(n deg f)` further operations.
+pub fn compose<T: PolynomialFieldElement>(
+    f: &Polynomial<T>,
+    g: &Polynomial<T>,
+    n: usize,
+    c: &Constants<T>,
+) -> Polynomial<T> {
+    let p = c.N;
+    let f = to_le(f.clone(), p);
+    let g = truncate(to_le(g.clone(), p), n);
+    if f.is_empty() || n == 0 {
+        return from_le(vec![]);
+    }
+    let k = (f.len() as f64).sqrt().ceil() as usize;
+
+    let mut pows = vec![vec![zero_mod(p) + T::from(1)]];
+    (1..=k).for_each(|i| pows.push(truncate(mul_poly(&pows[i - 1], &g, c), n)));
+    let blocks = f
+        .chunks(k)
+        .map(|block| {
+            block.iter().zip(&pows).fold(vec![], |acc, (&a, x)| {
+                let term = x.iter().map(|&y| (y * a).rem(p)).collect::<Vec<_>>();
+                add_poly(&acc, &trim(term), p)
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let out = blocks.iter().rev().fold(vec![], |acc, b| {
+        add_poly(&truncate(mul_poly(&acc, &pows[k], c), n), b, p)
+    });
+    from_le(truncate(out, n))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use crate::{
+        evaluation::compose,
+        ntt::Constants,
+        numbers::BigInt256,
+        polynomial::Polynomial,
+        test_utils::{constants, poly_be as poly, random_monic, P},
+    };
+
+    fn random_poly(n: usize, rng: &mut StdRng) -> Polynomial<BigInt256> {
+        Polynomial::new(random_monic(n, P, rng))
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let q = BigInt256::from(P);
+        // 3x^2 + 2x + 1
+        let f = poly(&[3, 2, 1]);
+        assert_eq!(f.evaluate(BigInt256::from(2)), BigInt256::from(17));
+        assert_eq!(
+            f.evaluate(BigInt256::from(1_u64 << 40)),
+            BigInt256::from(3 * (1_u128 << 80) + (2 << 40) + 1)
+        );
+        assert_eq!(poly(&[]).evaluate(BigInt256::from(5)), BigInt256::from(0));
+
+        assert_eq!(f.evaluate_mod(BigInt256::from(2), q), BigInt256::from(17));
+        assert_eq!(
+            f.evaluate_mod(BigInt256::from(P - 1), q),
+            BigInt256::from(2)
+        );
+        assert_eq!(
+            f.evaluate_mod(BigInt256::from(1_u64 << 40), q),
+            BigInt256::from(((3 * (1_u128 << 80) + (2 << 40) + 1) % P as u128) as u64)
+        );
+        assert_eq!(
+            poly(&[0]).evaluate_mod(BigInt256::from(5), q),
+            BigInt256::from(0)
+        );
+        // modulo an even `q`
+        assert_eq!(
+            f.evaluate_mod(BigInt256::from(5), BigInt256::from(16)),
+            BigInt256::from(86 % 16)
+        );
+    }
+
+    #[test]
+    fn test_taylor_shift() {
+        let c = constants();
+        let q = c.N;
+        let mut rng = StdRng::seed_from_u64(11);
+        for n in [1, 5, 200] {
+            let f = random_poly(n, &mut rng);
+            let a = BigInt256::from(rng.gen_range(0..P));
+            let g = f.taylor_shift(a, &c);
+            assert_eq!(g.coef.len(), n);
+            for _ in 0..4 {
+                let x = BigInt256::from(rng.gen_range(0..P));
+                let xa = (x + a).rem(q);
+                assert_eq!(g.evaluate_mod(x, q), f.evaluate_mod(xa, q));
+            }
+        }
+
+        // (x + 1)^2 = x^2 + 2x + 1, and the quadratic path for a degree above `p = 5`
+        let c = Constants {
+            N: BigInt256::from(5),
+            w: BigInt256::from(1),
+        };
+        assert_eq!(
+            poly(&[1, 0, 0]).taylor_shift(BigInt256::from(1), &c).coef,
+            poly(&[1, 2, 1]).coef
+        );
+        let f = poly(&[1, 2, 3, 4, 0, 1, 2, 3]);
+        let g = f.taylor_shift(BigInt256::from(3), &c);
+        for x in 0..5 {
+            assert_eq!(
+                g.evaluate_mod(BigInt256::from(x), c.N),
+                f.evaluate_mod(BigInt256::from((x + 3) % 5), c.N)
+            );
+        }
+    }
+
+    #[test]
+    fn test_compose() {
+        let c = constants();
+        let q = c.N;
+        // f(x) = x^2 + 1 at g(x) = x + 2 is x^2 + 4x + 5
+        let h = compose(&poly(&[1, 0, 1]), &poly(&[1, 2]), 10, &c);
+        assert_eq!(h.coef, poly(&[1, 4, 5]).coef);
+        // truncated as a power series
+        let h = compose(&poly(&[1, 0, 1]), &poly(&[1, 2]), 2, &c);
+        assert_eq!(h.coef, poly(&[4, 5]).coef);
+
+        // untruncated, the composition agrees with evaluation at every point; `g^10` is long
+        // enough that its products use the NTT
+        let mut rng = StdRng::seed_from_u64(13);
+        let f = random_poly(100, &mut rng);
+        let g = random_poly(20, &mut rng);
+        let h = compose(&f, &g, 99 * 19 + 1, &c);
+        assert_eq!(h.coef.len(), 99 * 19 + 1);
+        for _ in 0..4 {
+            let x = BigInt256::from(rng.gen_range(0..P));
+            assert_eq!(
+                h.evaluate_mod(x, q),
+                f.evaluate_mod(g.evaluate_mod(x, q), q)
+            );
+        }
+        assert_eq!(compose(&poly(&[0]), &g, 5, &c), poly(&[0]));
+    }
+}
@@ -0,0 +1,504 @@
+use std::{
+    cmp::Ordering,
+    fmt::{Debug, Display},
+    marker::PhantomData,
+    ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, ShrAssign, Sub},
+};
+
+use crypto_bigint::Invert;
+use itertools::Itertools;
+
+use crate::{
+    error::NttError, ntt::Constants, numbers::NttFieldElement, polynomial::PolynomialFieldElement,
+};
+
+/// Describes `F_p[x] / (f)` for a monic irreducible `f` of degree `D`.
+pub trait ExtConfig<T: PolynomialFieldElement, const D: usize>: Copy + Send + Sync {
+    /// The prime `p` of the base field.
+    fn modulus() -> T;
+    /// The low coefficients `[f_0, ..., f_(D-1)]` of `f = x^D + f_(D-1) x^(D-1) + ... + f_0`,
+    /// as integers below `p`. For `x^2 - r` this is `[p - r, 0]`.
+    fn irreducible() -> [T; D];
+}
+
+/// An element `coef[0] + coef[1] x + ... + coef[D-1] x^(D-1)` of a degree `D` extension of
+/// `F_p`. Every coefficient is kept under the modulus `p`.
+///
+/// The `NttFieldElement` methods treat the field as its own modulus: `rem` and `set_mod` leave
+/// the element unchanged, and `mod_exp` reads its exponent as the base-`p` number
+/// `coef[0] + coef[1] p + ... + coef[D-1] p^(D-1)`, which reaches every exponent modulo the
+/// group order `p^D - 1`. So the code in `ntt` and `polynomial` runs unchanged once the
+/// `Constants` are lifted with `Constants::lift`.
+#[derive(Clone, Copy)]
+pub struct Ext<T: PolynomialFieldElement, const D: usize, C: ExtConfig<T, D>> {
+    pub coef: [T; D],
+    config: PhantomData<C>,
+}
+
+impl<T: PolynomialFieldElement, const D: usize, C: ExtConfig<T, D>> Ext<T, D, C> {
+    /// Panics unless every coefficient is below `p`, see `try_new`.
+    pub fn new(coef: [T; D]) -> Self {
+        Self::try_new(coef).unwrap()
+    }
+
+    pub fn try_new(coef: [T; D]) -> Result<Self, NttError> {
+        let p = C::modulus();
+        if coef.iter().any(|&x| x >= p) {
+            return Err(NttError::Overflow);
+        }
+        let coef = coef.map(|mut x| {
+            x.set_mod(p).unwrap();
+            x
+        });
+        Ok(Ext {
+            coef,
+            config: PhantomData,
+        })
+    }
+
+    /// Embeds `x` as a constant. Panics unless `x` is below `p`, see `try_from_base`.
+    pub fn from_base(x: T) -> Self {
+        Self::try_from_base(x).unwrap()
+    }
+
+    pub fn try_from_base(x: T) -> Result<Self, NttError> {
+        let zero = T::from(0);
+        Self::try_new(std::array::from_fn(|i| if i == 0 { x } else { zero }))
+    }
+
+    fn map(self, f: impl Fn(T) -> T) -> Self {
+        Ext {
+            coef: self.coef.map(f),
+            config: PhantomData,
+        }
+    }
+
+    fn zip(self, rhs: Self, f: impl Fn(T, T) -> T) -> Self {
+        Ext {
+            coef: std::array::from_fn(|i| f(self.coef[i], rhs.coef[i])),
+            config: PhantomData,
+        }
+    }
+
+    // `self^e` for an exponent in the base field
+    fn pow_base(self, e: T) -> Self {
+        let mut res = Self::from(1_u32);
+        let mut b = self;
+        let mut e = e;
+        while !e.is_zero() {
+            if !e.is_even() {
+                res *= b;
+            }
+            b *= b;
+            e >>= 1;
+        }
+        res
+    }
+
+    // `self * x`, reducing `x^D` by the irreducible polynomial
+    fn mul_x(self) -> Self {
+        let f = C::irreducible();
+        let c = self.coef[D - 1];
+        Ext {
+            coef: std::array::from_fn(|i| {
+                if i == 0 {
+                    -(c * f[0])
+                } else {
+                    self.coef[i - 1] - c * f[i]
+                }
+            }),
+            config: PhantomData,
+        }
+    }
+}
+
+impl<T: PolynomialFieldElement> Constants<T> {
+    /// Embeds the modulus and root of unity in an extension of `F_N`, so that `forward`,
+    /// `inverse` and `fast_mul` can run over `Ext<T, D, C>`. `N` is zero in the extension, which
+    /// is its own modulus.
+    pub fn lift<const D: usize, C: ExtConfig<T, D>>(&self) -> Constants<Ext<T, D, C>> {
+        Constants {
+            N: Ext::from(0_u32),
+            w: Ext::from_base(self.w.rem(self.N)),
+        }
+    }
+}
+
+impl<T: PolynomialFieldElement, const D: usize, C: ExtConfig<T, D>> NttFieldElement
+    for Ext<T, D, C>
+{
+    fn set_mod(&mut self, _: Self) -> Result<(), NttError> {
+        Ok(())
+    }
+
+    fn rem(&self, _: Self) -> Self {
+        *self
+    }
+
+    fn pow(&self, n: u128) -> Self {
+        let mut res = Self::from(1_u32);
+        let mut b = *self;
+        let mut n = n;
+        while n > 0 {
+            if n & 1 == 1 {
+                res *= b;
+            }
+            b *= b;
+            n >>= 1;
+        }
+        res
+    }
+
+    // `self^(e_0 + e_1 p + ...)` as the product of `(self^(p^i))^(e_i)`, each `self^(p^i)` a
+    // further power of `p`
+    fn mod_exp(&self, exp: Self, _: Self) -> Self {
+        let p = C::modulus();
+        let Some(top) = exp.coef.iter().rposition(|x| !x.is_zero()) else {
+            return Self::from(1_u32);
+        };
+        let mut res = Self::from(1_u32);
+        let mut b = *self;
+        exp.coef[..=top].iter().enumerate().for_each(|(i, &e)| {
+            if i > 0 {
+                b = b.pow_base(p);
+            }
+            res *= b.pow_base(e);
+        });
+        res
+    }
+
+    fn is_even(&self) -> bool {
+        self.coef[0].is_even()
+    }
+
+    fn is_zero(&self) -> bool {
+        self.coef.iter().all(|x| x.is_zero())
+    }
+
+    fn to_words(&self) -> Vec<u64> {
+        self.coef.iter().flat_map(|x| x.to_words()).collect()
+    }
+}
+
+impl<T: PolynomialFieldElement, const D: usize, C: ExtConfig<T, D>> Add for Ext<T, D, C> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        self.zip(rhs, |a, b| a + b)
+    }
+}
+
+impl<T: PolynomialFieldElement, const D: usize, C: ExtConfig<T, D>> AddAssign for Ext<T, D, C> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<T: PolynomialFieldElement, const D: usize, C: ExtConfig<T, D>> Sub for Ext<T, D, C> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.zip(rhs, |a, b| a - b)
+    }
+}
+
+impl<T: PolynomialFieldElement, const D: usize, C: ExtConfig<T, D>> Neg for Ext<T, D, C> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        self.map(|a| -a)
+    }
+}
+
+impl<T: PolynomialFieldElement, const D: usize, C: ExtConfig<T, D>> Mul for Ext<T, D, C> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        // products go on the left so that the sums take the modulus `p` from them
+        let mut prod = vec![T::from(0); 2 * D - 1];
+        (0..D)
+            .cartesian_product(0..D)
+            .for_each(|(i, j)| prod[i + j] = self.coef[i] * rhs.coef[j] + prod[i + j]);
+
+        // x^D = -(f_0 + f_1 x + ... + f_(D-1) x^(D-1))
+        let f = C::irreducible();
+        (D..2 * D - 1).rev().for_each(|k| {
+            let c = prod[k];
+            (0..D).for_each(|i| prod[k - D + i] = prod[k - D + i] - c * f[i]);
+        });
+        Ext {
+            coef: std::array::from_fn(|i| prod[i]),
+            config: PhantomData,
+        }
+    }
+}
+
+impl<T: PolynomialFieldElement, const D: usize, C: ExtConfig<T, D>> MulAssign for Ext<T, D, C> {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl<T: PolynomialFieldElement, const D: usize, C: ExtConfig<T, D>> Div for Ext<T, D, C> {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Self) -> Self::Output {
+        self * rhs.invert()
+    }
+}
+
+impl<T: PolynomialFieldElement, const D: usize, C: ExtConfig<T, D>> Invert for Ext<T, D, C> {
+    type Output = Self;
+
+    // solves `self * y = 1` by Gaussian elimination on the matrix of multiplication by `self`,
+    // whose columns are `self * x^j`. Zero is returned unchanged.
+    fn invert(&self) -> Self::Output {
+        if self.is_zero() {
+            return *self;
+        }
+        let one = Self::from(1_u32).coef;
+        let mut cols = Vec::with_capacity(D);
+        let mut col = *self;
+        (0..D).for_each(|_| {
+            cols.push(col.coef);
+            col = col.mul_x();
+        });
+        let mut m = (0..D)
+            .map(|i| {
+                let mut row = (0..D).map(|j| cols[j][i]).collect_vec();
+                row.push(one[i]);
+                row
+            })
+            .collect_vec();
+
+        (0..D).for_each(|c| {
+            // `f` is irreducible, so the matrix is invertible and a pivot always exists
+            let pivot = (c..D).find(|&r| !m[r][c].is_zero()).unwrap();
+            m.swap(c, pivot);
+            let inv = m[c][c].invert();
+            m[c].iter_mut().for_each(|x| *x *= inv);
+            (0..D).filter(|&r| r != c).for_each(|r| {
+                let factor = m[r][c];
+                if !factor.is_zero() {
+                    (0..=D).for_each(|k| m[r][k] = m[r][k] - factor * m[c][k]);
+                }
+            });
+        });
+        Ext {
+            coef: std::array::from_fn(|i| m[i][D]),
+            config: PhantomData,
+        }
+    }
+}
+
+impl<T: PolynomialFieldElement, const D: usize, C: ExtConfig<T, D>> ShrAssign<usize>
+    for Ext<T, D, C>
+{
+    fn shr_assign(&mut self, rhs: usize) {
+        self.coef.iter_mut().for_each(|x| *x >>= rhs);
+    }
+}
+
+impl<T: PolynomialFieldElement, const D: usize, C: ExtConfig<T, D>> PartialEq for Ext<T, D, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.coef == other.coef
+    }
+}
+
+// lexicographic, starting from the highest coefficient
+impl<T: PolynomialFieldElement, const D: usize, C: ExtConfig<T, D>> PartialOrd for Ext<T, D, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.coef
+            .iter()
+            .zip(other.coef.iter())
+            .rev()
+            .map(|(a, b)| a.partial_cmp(b))
+            .find(|o| *o != Some(Ordering::Equal))
+            .unwrap_or(Some(Ordering::Equal))
+    }
+}
+
+impl<T: PolynomialFieldElement, const D: usize, C: ExtConfig<T, D>> Display for Ext<T, D, C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.coef[0])?;
+        self.coef[1..]
+            .iter()
+            .enumerate()
+            .try_for_each(|(i, x)| write!(f, " + {}x^{}", x, i + 1))
+    }
+}
+
+impl<T: PolynomialFieldElement, const D: usize, C: ExtConfig<T, D>> Debug for Ext<T, D, C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Ext({})", self)
+    }
+}
+
+macro_rules! impl_from {
+    ($($t:ty),*) => {
+        $(
+            impl<T: PolynomialFieldElement, const D: usize, C: ExtConfig<T, D>> From<$t>
+                for Ext<T, D, C>
+            {
+                fn from(value: $t) -> Self {
+                    Self::from_base(T::from(value))
+                }
+            }
+        )*
+    };
+}
+
+impl_from!(u16, u32, i32);
+
+// fails with `Overflow` wherever the base field cannot hold the value
+macro_rules! impl_try_from {
+    ($($t:ty),*) => {
+        $(
+            impl<T: PolynomialFieldElement, const D: usize, C: ExtConfig<T, D>> TryFrom<$t>
+                for Ext<T, D, C>
+            {
+                type Error = NttError;
+
+                fn try_from(value: $t) -> Result<Self, NttError> {
+                    T::try_from(value)
+                        .map(Self::from_base)
+                        .map_err(|_| NttError::Overflow)
+                }
+            }
+        )*
+    };
+}
+
+impl_try_from!(u64, u128, usize);
+
+impl<T: PolynomialFieldElement, const D: usize, C: ExtConfig<T, D>> PolynomialFieldElement
+    for Ext<T, D, C>
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use rand::Rng;
+
+    use crate::{
+        error::NttError,
+        extension::{Ext, ExtConfig},
+        ntt::{forward, inverse, Constants},
+        numbers::{BigInt64, NttFieldElement},
+        polynomial::{fast_mul, mul_brute, Polynomial},
+        prime::root_of_unity,
+    };
+    use crypto_bigint::Invert;
+
+    const BABY_BEAR: u32 = 2013265921;
+    const MERSENNE31: u32 = (1 << 31) - 1;
+
+    // BabyBear with `x^4 - 11`
+    #[derive(Clone, Copy)]
+    struct BabyBear4;
+
+    impl ExtConfig<BigInt64, 4> for BabyBear4 {
+        fn modulus() -> BigInt64 {
+            BigInt64::from(BABY_BEAR)
+        }
+
+        fn irreducible() -> [BigInt64; 4] {
+            [BABY_BEAR - 11, 0, 0, 0].map(BigInt64::from)
+        }
+    }
+
+    // Mersenne31 with `x^2 + 1`
+    #[derive(Clone, Copy)]
+    struct Complex31;
+
+    impl ExtConfig<BigInt64, 2> for Complex31 {
+        fn modulus() -> BigInt64 {
+            BigInt64::from(MERSENNE31)
+        }
+
+        fn irreducible() -> [BigInt64; 2] {
+            [1_u32, 0].map(BigInt64::from)
+        }
+    }
+
+    type F4 = Ext<BigInt64, 4, BabyBear4>;
+    type F2 = Ext<BigInt64, 2, Complex31>;
+
+    fn random<const D: usize, C: ExtConfig<BigInt64, D>>() -> Ext<BigInt64, D, C> {
+        Ext::new(std::array::from_fn(|_| {
+            BigInt64::from(rand::thread_rng().gen::<u32>() % BABY_BEAR.min(MERSENNE31))
+        }))
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let x = F4::new([0_u32, 1, 0, 0].map(BigInt64::from));
+        assert_eq!(x.pow(4), F4::from(11));
+        assert_eq!(x.pow(3) * x, x.mod_exp(F4::from(4), F4::from(0)));
+
+        let i = F2::new([0_u32, 1].map(BigInt64::from));
+        assert_eq!(i * i, -F2::from(1));
+        assert_eq!(
+            i.invert() * F2::from(5),
+            F2::new([0, MERSENNE31 - 5].map(BigInt64::from))
+        );
+
+        (0..20).for_each(|_| {
+            let (a, b, c) = (random::<4, BabyBear4>(), random(), random());
+            assert_eq!(a * a.invert(), F4::from(1));
+            assert_eq!((a * b) * c, a * (b * c));
+            assert_eq!(a * (b + c), a * b + a * c);
+            assert_eq!((a * b) / b, a);
+
+            let z = random::<2, Complex31>();
+            assert_eq!(z * z.invert(), F2::from(1));
+        });
+        assert!(F4::from(0).invert().is_zero());
+    }
+
+    #[test]
+    fn test_range() {
+        let p = BigInt64::from(BABY_BEAR);
+        assert_eq!(F4::try_from_base(p).unwrap_err(), NttError::Overflow);
+        assert_eq!(
+            F2::try_new([1, MERSENNE31].map(BigInt64::from)).unwrap_err(),
+            NttError::Overflow
+        );
+
+        // exponents are base-`p` digits: `x^p`, `x^(p + 2)`, and `x^(p^4 - 1) = 1`
+        let a = random::<4, BabyBear4>();
+        let digits = |e: [u32; 4]| F4::new(e.map(BigInt64::from));
+        assert_eq!(
+            a.mod_exp(digits([0, 1, 0, 0]), F4::from(0)),
+            a.pow(BABY_BEAR as u128)
+        );
+        assert_eq!(
+            a.mod_exp(digits([2, 1, 0, 0]), F4::from(0)),
+            a.pow(BABY_BEAR as u128 + 2)
+        );
+        let order = [BABY_BEAR - 1; 4];
+        assert_eq!(a.mod_exp(digits(order), F4::from(0)), F4::from(1));
+        assert_eq!(a.mod_exp(F4::from(0), F4::from(0)), F4::from(1));
+    }
+
+    #[test]
+    fn test_fast_mul() {
+        let n = 16;
+        let p = BigInt64::from(BABY_BEAR);
+        let c = Constants {
+            N: p,
+            w: root_of_unity(p, BigInt64::from(n)),
+        }
+        .lift::<4, BabyBear4>();
+
+        let v = (0..n).map(|_| random::<4, BabyBear4>()).collect_vec();
+        assert_eq!(inverse(forward(v.clone(), &c), &c), v);
+
+        let a = Polynomial::new(v[..7].to_vec());
+        let b = Polynomial::new(v[7..].to_vec());
+        let expected = mul_brute(a.clone(), b.clone()).coef;
+        assert_eq!(fast_mul(a, b, &c).coef, expected[..15]);
+    }
+}
@@ -0,0 +1,345 @@
+use std::cmp::Ordering;
+
+use rand::thread_rng;
+
+use crate::{
+    error::NttError,
+    gcd::xgcd_le,
+    multiply::mul_poly,
+    ntt::Constants,
+    poly_le::{
+        add_poly, bits, div_rem_poly, from_le, inv_mod, small, sub_mod, sub_poly, to_le, trim,
+        zero_mod,
+    },
+    polynomial::{Polynomial, PolynomialFieldElement},
+    prime::two_adic_root,
+    sampling::uniform,
+};
+
+// Polynomials are handled in the little-endian form of `poly_le`. The largest power-of-two root of
+// unity mod `p` lets products use the NTT wherever the transform length allows.
+fn field_constants<T: PolynomialFieldElement>(p: T) -> Constants<T> {
+    let one = T::from(1);
+    let mut n = one;
+    let mut q = p - one;
+    while !q.is_zero() && q.is_even() {
+        q >>= 1;
+        n *= T::from(2);
+    }
+    let w = if n == one { one } else { two_adic_root(p, n) };
+    Constants { N: p, w }
+}
+
+fn gcd<T: PolynomialFieldElement>(a: &[T], b: &[T], c: &Constants<T>) -> Vec<T> {
+    xgcd_le(a.to_vec(), b.to_vec(), c).0
+}
+
+fn monic<T: PolynomialFieldElement>(a: &[T], c: &Constants<T>) -> Vec<T> {
+    mul_poly(a, &[inv_mod(a[a.len() - 1], c.N)], c)
+}
+
+fn mul_mod<T: PolynomialFieldElement>(a: &[T], b: &[T], f: &[T], c: &Constants<T>) -> Vec<T> {
+    div_rem_poly(&mul_poly(a, b, c), f, c.N).1
+}
+
+fn pow_mod<T: PolynomialFieldElement>(a: &[T], e: T, f: &[T], c: &Constants<T>) -> Vec<T> {
+    let words = e.to_words();
+    let mut res = vec![zero_mod(c.N) + T::from(1)];
+    for i in (0..bits(e)).rev() {
+        res = mul_mod(&res, &res, f, c);
+        if (words[i / 64] >> (i % 64)) & 1 == 1 {
+            res = mul_mod(&res, a, f, c);
+        }
+    }
+    div_rem_poly(&res, f, c.N).1
+}
+
+fn x<T: PolynomialFieldElement>(p: T) -> Vec<T> {
+    let zero = zero_mod(p);
+    vec![zero, zero + T::from(1)]
+}
+
+fn derivative<T: PolynomialFieldElement>(a: &[T], p: T) -> Vec<T> {
+    let out = (1..a.len())
+        .map(|i| (a[i] * small(i)).rem(p))
+        .collect::<Vec<_>>();
+    trim(out)
+}
+
+// `f` must be monic. Returns square-free `g` with multiplicity `m`, whose product of `g^m` is `f`.
+fn square_free<T: PolynomialFieldElement>(f: &[T], c: &Constants<T>) -> Vec<(Vec<T>, usize)> {
+    let p = c.N;
+    let mut out = vec![];
+    let mut k = gcd(f, &derivative(f, p), c);
+    let mut w = div_rem_poly(f, &k, p).0;
+    let mut i = 1;
+    while w.len() > 1 {
+        let y = gcd(&w, &k, c);
+        let z = div_rem_poly(&w, &y, p).0;
+        if z.len() > 1 {
+            out.push((z, i));
+        }
+        k = div_rem_poly(&k, &y, p).0;
+        w = y;
+        i += 1;
+    }
+    // what is left is a `p`-th power, whose derivative vanishes, so `p <= deg k` fits a word
+    if k.len() > 1 {
+        let step = p.to_words()[0] as usize;
+        let root = k.iter().step_by(step).copied().collect::<Vec<_>>();
+        out.extend(
+            square_free(&root, c)
+                .into_iter()
+                .map(|(g, m)| (g, m * step)),
+        );
+    }
+    out
+}
+
+// `f` must be monic and square-free. Splits it into products of irreducibles of equal degree.
+fn distinct_degree<T: PolynomialFieldElement>(f: &[T], c: &Constants<T>) -> Vec<(Vec<T>, usize)> {
+    let p = c.N;
+    let mut out = vec![];
+    let mut f = f.to_vec();
+    let mut h = x(p);
+    let mut d = 1;
+    while f.len() > 2 * d {
+        h = pow_mod(&h, p, &f, c);
+        let g = gcd(&f, &sub_poly(&h, &x(p), p), c);
+        if g.len() > 1 {
+            f = div_rem_poly(&f, &g, p).0;
+            h = div_rem_poly(&h, &f, p).1;
+            out.push((g, d));
+        }
+        d += 1;
+    }
+    if f.len() > 1 {
+        let d = f.len() - 1;
+        out.push((f, d));
+    }
+    out
+}
+
+// Cantor-Zassenhaus: `f` is a monic product of distinct irreducibles of degree `d`.
+fn equal_degree<T: PolynomialFieldElement>(f: &[T], d: usize, c: &Constants<T>) -> Vec<Vec<T>> {
+    let p = c.N;
+    let one = T::from(1);
+    if f.len() - 1 == d {
+        return vec![f.to_vec()];
+    }
+    let mut rng = thread_rng();
+    loop {
+        let a = to_le(uniform(f.len() - 1, p, &mut rng), p);
+        if a.len() < 2 {
+            continue;
+        }
+        let b = if p == T::from(2) {
+            // the trace `a + a^2 + ... + a^(2^(d - 1))` is 0 or 1 modulo each factor
+            let mut u = a.clone();
+            let mut t = a.clone();
+            for _ in 1..d {
+                u = mul_mod(&u, &u, f, c);
+                t = add_poly(&t, &u, p);
+            }
+            t
+        } else {
+            // `a^((p^d - 1) / 2)` is `1` or `-1` modulo each factor, with the exponent
+            // split as `(1 + p + ... + p^(d - 1)) (p - 1) / 2`
+            let mut u = a.clone();
+            let mut s = a.clone();
+            for _ in 1..d {
+                u = pow_mod(&u, p, f, c);
+                s = mul_mod(&s, &u, f, c);
+            }
+            let b = pow_mod(&s, (p - one) / T::from(2), f, c);
+            sub_poly(&b, &[zero_mod(p) + one], p)
+        };
+        let g = gcd(f, &b, c);
+        if g.len() > 1 && g.len() < f.len() {
+            let mut out = equal_degree(&g, d, c);
+            out.extend(equal_degree(&div_rem_poly(f, &g, p).0, d, c));
+            return out;
+        }
+    }
+}
+
+fn by_degree<T: PolynomialFieldElement>(a: &[T], b: &[T]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().partial_cmp(b.iter().rev()).unwrap())
+}
+
+/// The monic irreducible factors of `f` over `F_p` with their multiplicities, by increasing
+/// degree. The leading coefficient of `f` is dropped.
+pub fn factor<T: PolynomialFieldElement>(
+    f: Polynomial<T>,
+    p: T,
+) -> Result<Vec<(Polynomial<T>, usize)>, NttError> {
+    let c = field_constants(p);
+    let f = to_le(f, p);
+    if f.is_empty() {
+        return Err(NttError::ZeroPolynomial);
+    }
+    let mut out = vec![];
+    for (g, m) in square_free(&monic(&f, &c), &c) {
+        for (h, d) in distinct_degree(&g, &c) {
+            out.extend(equal_degree(&h, d, &c).into_iter().map(|q| (q, m)));
+        }
+    }
+    out.sort_by(|(a, _), (b, _)| by_degree(a, b));
+    Ok(out.into_iter().map(|(q, m)| (from_le(q), m)).collect())
+}
+
+/// Whether `f` is irreducible over `F_p`, by Ben-Or's test. Constants are not.
+pub fn is_irreducible<T: PolynomialFieldElement>(f: Polynomial<T>, p: T) -> bool {
+    let c = field_constants(p);
+    let f = to_le(f, p);
+    if f.len() < 2 {
+        return false;
+    }
+    let f = monic(&f, &c);
+    // no factor of degree `d` exactly when `x^(p^d) - x` is coprime to `f`
+    let mut h = x(p);
+    (0..(f.len() - 1) / 2).all(|_| {
+        h = pow_mod(&h, p, &f, &c);
+        gcd(&f, &sub_poly(&h, &x(p), p), &c).len() == 1
+    })
+}
+
+/// The distinct roots of `f` in `F_p`, in increasing order.
+pub fn roots<T: PolynomialFieldElement>(f: Polynomial<T>, p: T) -> Result<Vec<T>, NttError> {
+    let c = field_constants(p);
+    let f = to_le(f, p);
+    if f.is_empty() {
+        return Err(NttError::ZeroPolynomial);
+    }
+    if f.len() == 1 {
+        return Ok(vec![]);
+    }
+    let f = monic(&f, &c);
+    // the product of the distinct linear factors is `gcd(f, x^p - x)`
+    let xp = pow_mod(&x(p), p, &f, &c);
+    let g = gcd(&f, &sub_poly(&xp, &x(p), p), &c);
+    if g.len() < 2 {
+        return Ok(vec![]);
+    }
+    let zero = T::from(0);
+    let mut out = equal_degree(&g, 1, &c)
+        .into_iter()
+        .map(|l| zero + sub_mod(zero_mod(p), l[0], p))
+        .collect::<Vec<_>>();
+    out.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::{
+        error::NttError,
+        factorization::{factor, is_irreducible, roots},
+        numbers::BigInt256,
+        polynomial::Polynomial,
+        test_utils::poly_be as poly,
+    };
+
+    fn product(fs: &[(Polynomial<BigInt256>, usize)], p: u64) -> Polynomial<BigInt256> {
+        // expands with `u64` arithmetic to stay independent of the code under test
+        let mut acc = vec![1_u64];
+        for (f, m) in fs {
+            let f = f
+                .coef
+                .iter()
+                .map(|x| x.to_u32().unwrap() as u64)
+                .collect_vec();
+            for _ in 0..*m {
+                let mut out = vec![0; acc.len() + f.len() - 1];
+                for (i, &a) in acc.iter().enumerate() {
+                    for (j, &b) in f.iter().enumerate() {
+                        out[i + j] = (out[i + j] + a * b) % p;
+                    }
+                }
+                acc = out;
+            }
+        }
+        Polynomial::new(acc.into_iter().map(BigInt256::from).collect())
+    }
+
+    #[test]
+    fn test_factor() {
+        // (x + 1)^7 (x^2 + 1)^2 (x^3 + x + 1) over F_7, where the first factor is `x^7 + 1`
+        let p = BigInt256::from(7);
+        let expected = vec![
+            (poly(&[1, 1]), 7),
+            (poly(&[1, 0, 1]), 2),
+            (poly(&[1, 0, 1, 1]), 1),
+        ];
+        // scaled, as the leading coefficient is dropped
+        let f = product(&expected, 7).coef;
+        let f = f.iter().map(|&x| (x * BigInt256::from(3)).rem(p)).collect();
+        let fs = factor(Polynomial::new(f), p).unwrap();
+        assert_eq!(fs, expected);
+
+        // x^3 + x^2 + x over F_2 is x (x^2 + x + 1)
+        let fs = factor(poly(&[1, 1, 1, 0]), BigInt256::from(2)).unwrap();
+        assert_eq!(fs.len(), 2);
+        assert_eq!(fs[1].0, poly(&[1, 1, 1]));
+
+        // a larger field, where products go through the NTT
+        let p = 998244353;
+        let parts = [
+            (poly(&[1, 5]), 2),
+            (poly(&[1, 0, 3]), 1),
+            (poly(&[1, 7, 0, 0, 11, 13, 2]), 3),
+        ];
+        let f = product(&parts, p);
+        let fs = factor(f.clone(), BigInt256::from(p)).unwrap();
+        assert_eq!(product(&fs, p), product(&parts, p));
+        assert!(fs
+            .iter()
+            .all(|(g, _)| is_irreducible(g.clone(), BigInt256::from(p))));
+        assert_eq!(
+            factor(poly(&[0]), BigInt256::from(p)).unwrap_err(),
+            NttError::ZeroPolynomial
+        );
+    }
+
+    #[test]
+    fn test_is_irreducible() {
+        assert!(is_irreducible(poly(&[1, 0, 1]), BigInt256::from(7)));
+        assert!(!is_irreducible(poly(&[1, 0, 1]), BigInt256::from(5)));
+        assert!(is_irreducible(poly(&[1, 1, 1]), BigInt256::from(2)));
+        assert!(!is_irreducible(poly(&[4]), BigInt256::from(7)));
+        // the extension moduli of the BabyBear and Mersenne-31 fields
+        let babybear = BigInt256::from(2013265921);
+        assert!(is_irreducible(poly(&[1, 0, 0, 0, 2013265910]), babybear));
+        assert!(!is_irreducible(poly(&[1, 0, 0, 0, 2013265920]), babybear));
+        assert!(is_irreducible(
+            poly(&[1, 0, 1]),
+            BigInt256::from(2147483647)
+        ));
+    }
+
+    #[test]
+    fn test_roots() {
+        let p = 998244353_u64;
+        let r = [3, 17, 123456789, 998244352];
+        let linear = r.iter().map(|&x| (poly(&[1, p - x]), 1)).collect_vec();
+        let mut parts = linear.clone();
+        parts.push((poly(&[1, 0, 3]), 1));
+        parts.push((linear[1].0.clone(), 2));
+        let f = product(&parts, p);
+        assert_eq!(
+            roots(f, BigInt256::from(p)).unwrap(),
+            r.map(BigInt256::from).to_vec()
+        );
+        assert!(roots(poly(&[1, 0, 3]), BigInt256::from(p))
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            roots(poly(&[1, 1, 0]), BigInt256::from(2)).unwrap().len(),
+            2
+        );
+    }
+}
@@ -0,0 +1,271 @@
+use crate::{
+    error::NttError,
+    multiply::mul_poly,
+    ntt::Constants,
+    poly_le::{add_poly, div_rem_poly, from_le, inv_mod, sub_poly, to_le, zero_mod},
+    polynomial::{Polynomial, PolynomialFieldElement},
+};
+
+// below these sizes the quadratic algorithms are faster
+const EUCLID_THRESHOLD: usize = 256;
+const HALF_GCD_THRESHOLD: usize = 800;
+
+// Polynomials are coefficient vectors over `Z_p` as in `poly_le`, and a `Mat` is a 2x2 matrix
+// of them.
+type Mat<T> = [[Vec<T>; 2]; 2];
+
+fn identity<T: PolynomialFieldElement>(p: T) -> Mat<T> {
+    let one = zero_mod(p) + T::from(1);
+    [[vec![one], vec![]], [vec![], vec![one]]]
+}
+
+fn mat_mul<T: PolynomialFieldElement>(x: &Mat<T>, y: &Mat<T>, c: &Constants<T>) -> Mat<T> {
+    let entry = |i: usize, j: usize| {
+        add_poly(
+            &mul_poly(&x[i][0], &y[0][j], c),
+            &mul_poly(&x[i][1], &y[1][j], c),
+            c.N,
+        )
+    };
+    [[entry(0, 0), entry(0, 1)], [entry(1, 0), entry(1, 1)]]
+}
+
+fn apply<T: PolynomialFieldElement>(m: &Mat<T>, a: &[T], b: &[T], c: &Constants<T>) -> [Vec<T>; 2] {
+    let row = |i: usize| add_poly(&mul_poly(&m[i][0], a, c), &mul_poly(&m[i][1], b, c), c.N);
+    [row(0), row(1)]
+}
+
+// one division step `(a, b) -> (b, a - q b)`, recorded in `m`
+fn euclid_step<T: PolynomialFieldElement>(
+    m: &mut Mat<T>,
+    a: &mut Vec<T>,
+    b: &mut Vec<T>,
+    c: &Constants<T>,
+) {
+    let (q, r) = div_rem_poly(a, b, c.N);
+    *a = std::mem::replace(b, r);
+    let [top, bottom] = std::mem::replace(m, identity(c.N));
+    let below = |j: usize| sub_poly(&top[j], &mul_poly(&q, &bottom[j], c), c.N);
+    *m = [[bottom[0].clone(), bottom[1].clone()], [below(0), below(1)]];
+}
+
+// For `deg a > deg b`, the product of the Euclidean steps that first bring `b` below
+// `a.len() / 2` coefficients. Only the top half of the inputs determines those quotients, which
+// is what makes the recursion subquadratic.
+fn half_gcd<T: PolynomialFieldElement>(a: &[T], b: &[T], c: &Constants<T>) -> Mat<T> {
+    let m = a.len() / 2;
+    let mut r = identity(c.N);
+    if b.len() <= m {
+        return r;
+    }
+    if a.len() <= EUCLID_THRESHOLD {
+        let (mut a, mut b) = (a.to_vec(), b.to_vec());
+        while b.len() > m {
+            euclid_step(&mut r, &mut a, &mut b, c);
+        }
+        return r;
+    }
+
+    let mut r = half_gcd(&a[m..], &b[m..], c);
+    let [mut a, mut b] = apply(&r, a, b, c);
+    if b.len() <= m {
+        return r;
+    }
+    euclid_step(&mut r, &mut a, &mut b, c);
+    if b.len() <= m {
+        return r;
+    }
+    let k = 2 * m - (a.len() - 1);
+    let s = half_gcd(&a[k..], &b[k..], c);
+    mat_mul(&s, &r, c)
+}
+
+// a matrix `m` with `m * (a, b) = (g, 0)` for `g` a gcd of `a` and `b`, along with `g`
+fn gcd_matrix<T: PolynomialFieldElement>(
+    a: Vec<T>,
+    b: Vec<T>,
+    c: &Constants<T>,
+    fast: bool,
+) -> (Mat<T>, Vec<T>) {
+    let mut res = identity(c.N);
+    let (mut a, mut b) = (a, b);
+    if !b.is_empty() && b.len() >= a.len() {
+        euclid_step(&mut res, &mut a, &mut b, c);
+    }
+    while !b.is_empty() {
+        if fast {
+            let m = half_gcd(&a, &b, c);
+            [a, b] = apply(&m, &a, &b, c);
+            res = mat_mul(&m, &res, c);
+            if b.is_empty() {
+                break;
+            }
+        }
+        euclid_step(&mut res, &mut a, &mut b, c);
+    }
+    (res, a)
+}
+
+pub(crate) fn xgcd_le<T: PolynomialFieldElement>(
+    a: Vec<T>,
+    b: Vec<T>,
+    c: &Constants<T>,
+) -> (Vec<T>, Vec<T>, Vec<T>) {
+    let fast = a.len().max(b.len()) > HALF_GCD_THRESHOLD;
+    let ([[s, t], _], g) = gcd_matrix(a, b, c, fast);
+    let Some(&lead) = g.last() else {
+        return (g, s, t);
+    };
+    let k = [inv_mod(lead, c.N)];
+    (
+        mul_poly(&g, &k, c),
+        mul_poly(&s, &k, c),
+        mul_poly(&t, &k, c),
+    )
+}
+
+/// Quotient and remainder of `a / b` over `Z_p`, for a prime `p`.
+pub fn div_rem<T: PolynomialFieldElement>(
+    a: Polynomial<T>,
+    b: Polynomial<T>,
+    p: T,
+) -> Result<(Polynomial<T>, Polynomial<T>), NttError> {
+    let b = to_le(b, p);
+    if b.is_empty() {
+        return Err(NttError::DivisionByZero);
+    }
+    let (q, r) = div_rem_poly(&to_le(a, p), &b, p);
+    Ok((from_le(q), from_le(r)))
+}
+
+/// The monic greatest common divisor of `a` and `b` over `Z_p`, where `p = c.N`. Large
+/// inputs use the half-GCD algorithm with NTT multiplication, small ones plain Euclid.
+pub fn gcd<T: PolynomialFieldElement>(
+    a: Polynomial<T>,
+    b: Polynomial<T>,
+    c: &Constants<T>,
+) -> Polynomial<T> {
+    xgcd(a, b, c).0
+}
+
+/// Returns `(g, s, t)` with `g` the monic gcd and `s a + t b = g`, where `deg s < deg b - deg g`
+/// and `deg t < deg a - deg g`.
+pub fn xgcd<T: PolynomialFieldElement>(
+    a: Polynomial<T>,
+    b: Polynomial<T>,
+    c: &Constants<T>,
+) -> (Polynomial<T>, Polynomial<T>, Polynomial<T>) {
+    let (g, s, t) = xgcd_le(to_le(a, c.N), to_le(b, c.N), c);
+    (from_le(g), from_le(s), from_le(t))
+}
+
+/// The inverse of `a` modulo `m` over `Z_p`, where `p = c.N`.
+pub fn modular_inverse<T: PolynomialFieldElement>(
+    a: Polynomial<T>,
+    m: Polynomial<T>,
+    c: &Constants<T>,
+) -> Result<Polynomial<T>, NttError> {
+    let m = to_le(m, c.N);
+    if m.is_empty() {
+        return Err(NttError::DivisionByZero);
+    }
+    let (_, a) = div_rem_poly(&to_le(a, c.N), &m, c.N);
+    let (g, s, _) = xgcd_le(a, m.clone(), c);
+    if g.len() != 1 {
+        return Err(NttError::NotInvertible);
+    }
+    Ok(from_le(div_rem_poly(&s, &m, c.N).1))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::{
+        error::NttError,
+        gcd::{div_rem, gcd, gcd_matrix, modular_inverse, xgcd},
+        multiply::mul_poly,
+        numbers::BigInt256,
+        poly_le::{div_rem_poly, from_le, sub_poly, to_le},
+        test_utils::{constants, poly_be, random_monic, P},
+    };
+
+    #[test]
+    fn test_div_rem() {
+        let p = BigInt256::from(17);
+        // x^3 + 2x + 5 = (x + 3)(x^2 + 14x + 11) + 6
+        let (q, r) = div_rem(poly_be(&[1, 0, 2, 5]), poly_be(&[1, 3]), p).unwrap();
+        assert_eq!(q.coef, poly_be(&[1, 14, 11]).coef);
+        assert_eq!(r.coef, poly_be(&[6]).coef);
+        assert_eq!(
+            div_rem(poly_be(&[1, 2]), poly_be(&[0]), p).unwrap_err(),
+            NttError::DivisionByZero
+        );
+    }
+
+    #[test]
+    fn test_xgcd() {
+        let c = constants();
+        let p = c.N;
+        assert_eq!(p, BigInt256::from(P));
+        let mut rng = StdRng::seed_from_u64(3);
+
+        // `gcd_matrix` is called with `fast` set below, so the last two pairs reach the half-GCD
+        // recursion without going past `HALF_GCD_THRESHOLD`
+        for (n, m, k) in [(5, 3, 1), (40, 30, 4), (300, 250, 20), (300, 300, 1)] {
+            let g = random_monic(k, 1000, &mut rng);
+            let a = mul_poly(&random_monic(n, 1000, &mut rng), &g, &c);
+            let b = mul_poly(&random_monic(m, 1000, &mut rng), &g, &c);
+            let (d, s, t) = xgcd(from_le(a.clone()), from_le(b.clone()), &c);
+            let (d, s, t) = (to_le(d, p), to_le(s, p), to_le(t, p));
+
+            // random polynomials are coprime with good probability, so the gcd is `g`
+            assert_eq!(d, g);
+            let lhs = mul_poly(&s, &a, &c);
+            assert_eq!(sub_poly(&d, &mul_poly(&t, &b, &c), p), lhs);
+            assert!(s.len() + d.len() <= b.len() && t.len() + d.len() <= a.len());
+
+            // half-GCD follows the same quotient sequence as Euclid
+            assert!(gcd_matrix(a.clone(), b.clone(), &c, true) == gcd_matrix(a, b, &c, false));
+        }
+
+        let zero = || from_le(vec![]);
+        let a = from_le(vec![BigInt256::from(6), BigInt256::from(3)]);
+        assert_eq!(
+            gcd(a.clone(), zero(), &c).coef,
+            vec![BigInt256::from(2), BigInt256::from(1)]
+        );
+        assert_eq!(gcd(zero(), zero(), &c), zero());
+    }
+
+    #[test]
+    fn test_modular_inverse() {
+        let c = constants();
+        let p = c.N;
+        let mut rng = StdRng::seed_from_u64(5);
+        let m = random_monic(200, 1 << 30, &mut rng);
+        let a = random_monic(150, 1 << 30, &mut rng);
+        let inv = to_le(
+            modular_inverse(from_le(a.clone()), from_le(m.clone()), &c).unwrap(),
+            p,
+        );
+        let (_, r) = div_rem_poly(&mul_poly(&a, &inv, &c), &m, p);
+        assert_eq!(r, vec![BigInt256::from(1)]);
+
+        // x^2 - 1 shares the factor x - 1 with x^2 - 2x + 1
+        let m = vec![
+            p - BigInt256::from(1),
+            BigInt256::from(0),
+            BigInt256::from(1),
+        ];
+        let a = vec![
+            BigInt256::from(1),
+            p - BigInt256::from(2),
+            BigInt256::from(1),
+        ];
+        assert_eq!(
+            modular_inverse(from_le(a), from_le(m), &c).unwrap_err(),
+            NttError::NotInvertible
+        );
+    }
+}
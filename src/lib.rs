@@ -1,4 +1,19 @@
+pub mod arithmetic;
+pub mod certificate;
+pub mod encoding;
+pub mod error;
+pub mod evaluation;
+pub mod extension;
+pub mod factorization;
+pub mod gcd;
+pub mod multiply;
 pub mod ntt;
 pub mod numbers;
+mod poly_le;
 pub mod polynomial;
 pub mod prime;
+pub mod recurrence;
+pub mod residue;
+pub mod sampling;
+#[cfg(test)]
+mod test_utils;
@@ -0,0 +1,386 @@
+use std::time::{Duration, Instant};
+
+use rand::thread_rng;
+
+use crate::{
+    ntt::{forward, inverse, Constants},
+    poly_le::{add_poly, from_le, sub_poly, to_le, transform_constants, trim, zero_mod},
+    polynomial::{fast_mul, Polynomial, PolynomialFieldElement},
+    sampling::uniform,
+};
+
+// sizes tried by `MulThresholds::tune`, growing by half each step
+const TUNE_SIZES: [usize; 15] = [
+    4, 6, 9, 13, 19, 28, 42, 63, 94, 141, 211, 316, 474, 711, 1066,
+];
+
+/// The operand lengths, of the shorter operand, above which each algorithm takes over from the
+/// one before it: schoolbook, then Karatsuba, then Toom-3. Above `ntt` too, the NTT is used
+/// whenever `c.w` has a large enough order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MulThresholds {
+    pub karatsuba: usize,
+    pub toom3: usize,
+    pub ntt: usize,
+}
+
+impl Default for MulThresholds {
+    // as tuned for `BigInt256` modulo a 30-bit prime
+    fn default() -> Self {
+        MulThresholds {
+            karatsuba: 42,
+            toom3: 94,
+            ntt: 42,
+        }
+    }
+}
+
+impl MulThresholds {
+    /// Measures the crossovers on random polynomials modulo `c.N`, as the first of the sizes
+    /// `4, 6, 9, ..., 1066` at which splitting once more beats the algorithm below. A crossover
+    /// that is never reached stays at `usize::MAX`. The results depend on the build profile, so
+    /// this belongs in release builds and benches.
+    pub fn tune<T: PolynomialFieldElement>(c: &Constants<T>) -> Self {
+        let p = c.N;
+        let mut rng = thread_rng();
+        let mut t = MulThresholds {
+            karatsuba: usize::MAX,
+            toom3: usize::MAX,
+            ntt: usize::MAX,
+        };
+
+        // the best of a few runs, with fewer at larger sizes
+        let mut time = |n: usize, t: &MulThresholds| {
+            let a = to_le(uniform(n, p, &mut rng), p);
+            let b = to_le(uniform(n, p, &mut rng), p);
+            (0..3 + 256 / n)
+                .map(|_| {
+                    let start = Instant::now();
+                    mul_le(&a, &b, c, t);
+                    start.elapsed()
+                })
+                .min()
+                .unwrap_or(Duration::ZERO)
+        };
+        // whether `set` moving the threshold below `n` makes length `n` faster
+        let mut pays_off = |t: MulThresholds, set: fn(&mut MulThresholds, usize), n: usize| {
+            let (mut below, mut above) = (t, t);
+            set(&mut below, n);
+            set(&mut above, n - 1);
+            time(n, &above) < time(n, &below)
+        };
+
+        // Toom-3 and the NTT only see operands above the schoolbook threshold
+        t.karatsuba =
+            crossover(0, |n| pays_off(t, |t, n| t.karatsuba = n, n)).unwrap_or(usize::MAX);
+        t.toom3 =
+            crossover(t.karatsuba, |n| pays_off(t, |t, n| t.toom3 = n, n)).unwrap_or(usize::MAX);
+        t.ntt = crossover(t.karatsuba, |n| pays_off(t, |t, n| t.ntt = n, n)).unwrap_or(usize::MAX);
+        t
+    }
+}
+
+// the size before the first of `TUNE_SIZES` above `from` at which `faster` holds
+fn crossover(from: usize, mut faster: impl FnMut(usize) -> bool) -> Option<usize> {
+    TUNE_SIZES
+        .windows(2)
+        .skip_while(|w| w[0] < from)
+        .find_map(|w| faster(w[1]).then_some(w[0]))
+}
+
+fn schoolbook<T: PolynomialFieldElement>(a: &[T], b: &[T], p: T) -> Vec<T> {
+    let mut out = vec![zero_mod(p); a.len() + b.len() - 1];
+    a.iter().enumerate().for_each(|(i, &x)| {
+        b.iter()
+            .enumerate()
+            .for_each(|(j, &y)| out[i + j] = (out[i + j] + (x * y).rem(p)).rem(p))
+    });
+    trim(out)
+}
+
+// `out += v x^shift`
+fn add_at<T: PolynomialFieldElement>(out: &mut Vec<T>, v: &[T], shift: usize, p: T) {
+    if out.len() < shift + v.len() {
+        out.resize(shift + v.len(), zero_mod(p));
+    }
+    out[shift..]
+        .iter_mut()
+        .zip(v)
+        .for_each(|(x, &y)| *x = (*x + y).rem(p));
+}
+
+fn scale<T: PolynomialFieldElement>(a: &[T], s: T, p: T) -> Vec<T> {
+    trim(a.iter().map(|&x| (x * s).rem(p)).collect())
+}
+
+// the `i`-th block of `k` coefficients
+fn part<T: PolynomialFieldElement>(a: &[T], i: usize, k: usize) -> &[T] {
+    &a[(i * k).min(a.len())..((i + 1) * k).min(a.len())]
+}
+
+fn karatsuba_le<T: PolynomialFieldElement>(
+    a: &[T],
+    b: &[T],
+    c: &Constants<T>,
+    t: &MulThresholds,
+) -> Vec<T> {
+    let p = c.N;
+    let k = a.len().max(b.len()).div_ceil(2);
+    let (a0, a1) = (part(a, 0, k), part(a, 1, k));
+    let (b0, b1) = (part(b, 0, k), part(b, 1, k));
+    let z0 = mul_le(a0, b0, c, t);
+    let z2 = mul_le(a1, b1, c, t);
+    let z1 = mul_le(&add_poly(a0, a1, p), &add_poly(b0, b1, p), c, t);
+    let z1 = sub_poly(&sub_poly(&z1, &z0, p), &z2, p);
+
+    let mut out = z0;
+    add_at(&mut out, &z1, k, p);
+    add_at(&mut out, &z2, 2 * k, p);
+    trim(out)
+}
+
+// evaluates at `0, 1, -1, -2` and infinity, and interpolates with Bodrato's sequence, which
+// divides by 2 and 3, so `gcd(p, 6) = 1`
+fn toom3_le<T: PolynomialFieldElement>(
+    a: &[T],
+    b: &[T],
+    c: &Constants<T>,
+    t: &MulThresholds,
+) -> Vec<T> {
+    let p = c.N;
+    let k = a.len().max(b.len()).div_ceil(3);
+    let (one, three) = (T::from(1), T::from(3));
+    let two = zero_mod(p) + T::from(2);
+    // inverses without Fermat, as `p` need not be prime
+    let half = zero_mod(p) + (p + one) / T::from(2);
+    let third = if p.rem(three) == one {
+        zero_mod(p) + (p + p + one) / three
+    } else {
+        zero_mod(p) + (p + one) / three
+    };
+
+    let eval = |v: &[T]| {
+        let (v0, v1, v2) = (part(v, 0, k), part(v, 1, k), part(v, 2, k));
+        let s = add_poly(v0, v2, p);
+        let minus_one = sub_poly(&s, v1, p);
+        let minus_two = sub_poly(&scale(&add_poly(&minus_one, v2, p), two, p), v0, p);
+        [add_poly(&s, v1, p), minus_one, minus_two]
+    };
+    let [a1, am1, am2] = eval(a);
+    let [b1, bm1, bm2] = eval(b);
+    let r0 = mul_le(part(a, 0, k), part(b, 0, k), c, t);
+    let r1 = mul_le(&a1, &b1, c, t);
+    let rm1 = mul_le(&am1, &bm1, c, t);
+    let rm2 = mul_le(&am2, &bm2, c, t);
+    let rinf = mul_le(part(a, 2, k), part(b, 2, k), c, t);
+
+    let r3 = scale(&sub_poly(&rm2, &r1, p), third, p);
+    let r1 = scale(&sub_poly(&r1, &rm1, p), half, p);
+    let r2 = sub_poly(&rm1, &r0, p);
+    let r3 = add_poly(
+        &scale(&sub_poly(&r2, &r3, p), half, p),
+        &scale(&rinf, two, p),
+        p,
+    );
+    let r2 = sub_poly(&add_poly(&r2, &r1, p), &rinf, p);
+    let r1 = sub_poly(&r1, &r3, p);
+
+    let mut out = r0;
+    add_at(&mut out, &r1, k, p);
+    add_at(&mut out, &r2, 2 * k, p);
+    add_at(&mut out, &r3, 3 * k, p);
+    add_at(&mut out, &rinf, 4 * k, p);
+    trim(out)
+}
+
+// The product over `Z_p` of coefficient vectors laid out as in `poly_le`, by the algorithm `t`
+// picks for the length of the shorter operand.
+pub(crate) fn mul_le<T: PolynomialFieldElement>(
+    a: &[T],
+    b: &[T],
+    c: &Constants<T>,
+    t: &MulThresholds,
+) -> Vec<T> {
+    let len = |v: &[T]| v.iter().rposition(|x| !x.is_zero()).map_or(0, |i| i + 1);
+    let (a, b) = (&a[..len(a)], &b[..len(b)]);
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+    let p = c.N;
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let n = short.len();
+    if n <= t.karatsuba {
+        return schoolbook(long, short, p);
+    }
+    if n > t.ntt {
+        let size = (a.len() + b.len()).next_power_of_two();
+        if let Some(c) = transform_constants(c, size) {
+            let out = fast_mul(Polynomial::new(a.to_vec()), Polynomial::new(b.to_vec()), &c);
+            return trim(out.coef);
+        }
+    }
+    // unbalanced operands are cut into products of `n` by `n`
+    if 2 * n <= long.len() {
+        let mut out = vec![];
+        long.chunks(n)
+            .enumerate()
+            .for_each(|(i, chunk)| add_at(&mut out, &mul_le(chunk, short, c, t), i * n, p));
+        return trim(out);
+    }
+    if n > t.toom3 && !p.is_even() && !p.rem(T::from(3)).is_zero() {
+        return toom3_le(a, b, c, t);
+    }
+    karatsuba_le(a, b, c, t)
+}
+
+pub(crate) fn mul_poly<T: PolynomialFieldElement>(a: &[T], b: &[T], c: &Constants<T>) -> Vec<T> {
+    mul_le(a, b, c, &MulThresholds::default())
+}
+
+// `mul_poly(a, a, c)`, with one forward transform instead of two
+pub(crate) fn sqr_poly<T: PolynomialFieldElement>(a: &[T], c: &Constants<T>) -> Vec<T> {
+    let p = c.N;
+    let n = (2 * a.len()).next_power_of_two();
+    let t = (a.len() > MulThresholds::default().ntt)
+        .then(|| transform_constants(c, n))
+        .flatten();
+    let Some(c) = t else {
+        return mul_poly(a, a, c);
+    };
+    let mut v = a.to_vec();
+    v.resize(n, zero_mod(p));
+    let sq = forward(v, &c).iter().map(|&x| (x * x).rem(p)).collect();
+    let mut out = inverse(sq, &c);
+    out.truncate(2 * a.len() - 1);
+    trim(out)
+}
+
+/// The product of `lhs` and `rhs` over `Z_p`, with `p = c.N`, by whichever algorithm the
+/// default thresholds pick for their size.
+pub fn mul<T: PolynomialFieldElement>(
+    lhs: Polynomial<T>,
+    rhs: Polynomial<T>,
+    c: &Constants<T>,
+) -> Polynomial<T> {
+    mul_with(lhs, rhs, c, &MulThresholds::default())
+}
+
+/// `mul` with the given thresholds, as from `MulThresholds::tune`.
+pub fn mul_with<T: PolynomialFieldElement>(
+    lhs: Polynomial<T>,
+    rhs: Polynomial<T>,
+    c: &Constants<T>,
+    t: &MulThresholds,
+) -> Polynomial<T> {
+    let p = c.N;
+    from_le(mul_le(&to_le(lhs, p), &to_le(rhs, p), c, t))
+}
+
+/// The product by Karatsuba's method down to the default schoolbook threshold.
+pub fn karatsuba<T: PolynomialFieldElement>(
+    lhs: Polynomial<T>,
+    rhs: Polynomial<T>,
+    c: &Constants<T>,
+) -> Polynomial<T> {
+    let t = MulThresholds {
+        toom3: usize::MAX,
+        ntt: usize::MAX,
+        ..Default::default()
+    };
+    mul_with(lhs, rhs, c, &t)
+}
+
+/// The product by Toom-Cook-3 down to the default schoolbook threshold. `c.N` must be prime
+/// to 6, or this is Karatsuba.
+pub fn toom3<T: PolynomialFieldElement>(
+    lhs: Polynomial<T>,
+    rhs: Polynomial<T>,
+    c: &Constants<T>,
+) -> Polynomial<T> {
+    let karatsuba = MulThresholds::default().karatsuba;
+    let t = MulThresholds {
+        karatsuba,
+        toom3: karatsuba,
+        ntt: usize::MAX,
+    };
+    mul_with(lhs, rhs, c, &t)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::{
+        multiply::{crossover, karatsuba, mul, mul_with, toom3, MulThresholds},
+        ntt::Constants,
+        numbers::BigInt256,
+        polynomial::{mul_brute, Polynomial},
+        sampling::uniform,
+        test_utils::{constants, reduced},
+    };
+
+    #[test]
+    fn test_algorithms() {
+        let c = constants();
+        let p = c.N;
+        let mut rng = StdRng::seed_from_u64(9);
+        // balanced and unbalanced, odd and even, across every threshold
+        for (n, m) in [
+            (1, 1),
+            (7, 3),
+            (40, 40),
+            (41, 17),
+            (150, 149),
+            (300, 90),
+            (700, 500),
+        ] {
+            let a = uniform(n, p, &mut rng);
+            let b = uniform(m, p, &mut rng);
+            let expected = reduced(mul_brute(a.clone(), b.clone()), p);
+            assert_eq!(mul(a.clone(), b.clone(), &c), expected);
+            assert_eq!(karatsuba(a.clone(), b.clone(), &c), expected);
+            assert_eq!(toom3(a.clone(), b.clone(), &c), expected);
+
+            // every algorithm at every level
+            let t = MulThresholds {
+                karatsuba: 2,
+                toom3: 5,
+                ntt: 60,
+            };
+            assert_eq!(mul_with(a, b, &c, &t), expected);
+        }
+
+        // a root of too small an order leaves the NTT out, and a modulus divisible by 3 leaves
+        // out Toom-3, which works for composite moduli prime to 6 too
+        for q in [1000003_u32, 3, 15, 21, 35] {
+            let c = Constants {
+                N: BigInt256::from(q),
+                w: BigInt256::from(q - 1),
+            };
+            let a = uniform(200, c.N, &mut rng);
+            let b = uniform(180, c.N, &mut rng);
+            let expected = reduced(mul_brute(a.clone(), b.clone()), c.N);
+            assert_eq!(mul(a.clone(), b.clone(), &c), expected);
+            assert_eq!(toom3(a, b, &c), expected);
+        }
+        assert_eq!(
+            mul(Polynomial::new(vec![]), uniform(5, p, &mut rng), &c),
+            Polynomial::new(vec![])
+        );
+    }
+
+    #[test]
+    fn test_crossover() {
+        // splitting first pays off at 42, so the threshold is the size below it
+        assert_eq!(crossover(0, |n| n >= 42), Some(28));
+        assert_eq!(crossover(0, |_| true), Some(4));
+        // sizes below `from` are never tried
+        let mut tried = vec![];
+        let t = crossover(50, |n| {
+            tried.push(n);
+            n > 100
+        });
+        assert_eq!((t, tried), (Some(94), vec![94, 141]));
+        assert_eq!(crossover(0, |_| false), None);
+    }
+}
@@ -1,76 +1,60 @@
-use crate::{numbers::BigInt, polynomial::PolynomialFieldElement, prime::is_prime};
+use crate::{
+    certificate::{pocklington_certificate, Certificate},
+    error::NttError,
+    numbers::BigInt,
+    poly_le::{bits, small, transform_constants},
+    polynomial::PolynomialFieldElement,
+    prime::{is_prime, try_root_of_unity},
+};
 use crypto_bigint::Invert;
 use itertools::Itertools;
 use rayon::prelude::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Constants<T: PolynomialFieldElement> {
     pub N: T,
     pub w: T,
 }
 
-fn prime_factors<T: PolynomialFieldElement>(a: T) -> Vec<T> {
-    let mut ans: Vec<T> = Vec::new();
-    let ZERO = T::from(0);
-    let ONE = T::from(1);
-    let mut x = T::from(2);
-    while x * x <= a {
-        if a.rem(x) == ZERO {
-            ans.push(x);
-        }
-        x += ONE;
+impl<T: PolynomialFieldElement> Constants<T> {
+    /// Proves that the modulus `N` is prime, using the known factors of `N - 1`.
+    pub fn certificate(&self) -> Option<Certificate<T>> {
+        pocklington_certificate(self.N)
     }
-    ans
-}
-
-#[cfg(feature = "parallel")]
-fn is_primitive_root<T: PolynomialFieldElement>(a: T, deg: T, N: T) -> bool {
-    let lhs = a.mod_exp(deg, N);
-    let ONE = T::from(1);
-    let lhs = lhs == ONE;
-    let rhs = prime_factors(deg)
-        .par_iter()
-        .map(|&x| a.mod_exp(deg / x, N) != ONE)
-        .all(|x| x);
-    lhs && rhs
 }
 
-#[cfg(not(feature = "parallel"))]
-fn is_primitive_root<T: PolynomialFieldElement>(a: T, deg: T, N: T) -> bool {
-    let lhs = a.mod_exp(deg, N);
-    let ONE = T::from(1);
-    let lhs = lhs == ONE;
-    let rhs = prime_factors(deg)
-        .iter()
-        .map(|&x| a.mod_exp(deg / x, N) != ONE)
-        .all(|x| x);
-    lhs && rhs
+pub fn working_modulus<T: PolynomialFieldElement>(n: T, M: T) -> Constants<T> {
+    try_working_modulus(n, M).unwrap()
 }
 
-pub fn working_modulus<T: PolynomialFieldElement>(n: T, M: T) -> Constants<T> {
-    let ZERO = T::from(0);
+pub fn try_working_modulus<T: PolynomialFieldElement>(
+    n: T,
+    M: T,
+) -> Result<Constants<T>, NttError> {
     let ONE = T::from(1);
-    let mut N = M;
-    if N >= ONE {
-        N = N * n + ONE;
-        while !is_prime(N) {
-            N += n;
-        }
+    if n.is_zero() || M < ONE {
+        return Err(NttError::NoPrime);
+    }
+    // products of two residues must fit in `T`, and so must `M * n` on the way there
+    let half = 32 * n.to_words().len();
+    if bits(M) + bits(n) > half + 1 {
+        return Err(NttError::Overflow);
     }
-    let totient = N - ONE;
-    assert!(N >= M);
-    let mut gen = T::from(0);
-    let mut g = T::from(2);
-    while g < N {
-        if is_primitive_root(g, totient, N) {
-            gen = g;
-            break;
+    let mut N = M * n + ONE;
+    while !is_prime(N) {
+        if bits(N) > half {
+            return Err(NttError::Overflow);
         }
-        g += ONE;
+        N += n;
     }
-    assert!(gen > ZERO);
-    let w = gen.mod_exp(totient / n, N);
-    Constants { N, w }
+    if bits(N) > half {
+        return Err(NttError::Overflow);
+    }
+    let w = try_root_of_unity(N, n)?;
+    Ok(Constants { N, w })
 }
 
 fn order_reverse<T: PolynomialFieldElement>(inp: &mut Vec<T>) {
@@ -90,20 +74,42 @@ fn order_reverse<T: PolynomialFieldElement>(inp: &mut Vec<T>) {
     });
 }
 
+#[cfg(not(feature = "constant-time"))]
+fn butterfly<T: PolynomialFieldElement>(lo: &mut T, hi: &mut T, MOD: T) {
+    let neg = if *lo < *hi {
+        (MOD + *lo) - *hi
+    } else {
+        *lo - *hi
+    };
+    *lo = if *lo + *hi >= MOD {
+        (*lo + *hi) - MOD
+    } else {
+        *lo + *hi
+    };
+    *hi = neg;
+}
+
+// both outputs are reduced unconditionally, so no branch depends on the coefficients
+#[cfg(feature = "constant-time")]
+fn butterfly<T: PolynomialFieldElement>(lo: &mut T, hi: &mut T, MOD: T) {
+    let neg = ((MOD + *lo) - *hi).rem(MOD);
+    *lo = (*lo + *hi).rem(MOD);
+    *hi = neg;
+}
+
 #[cfg(feature = "parallel")]
 fn fft<T: PolynomialFieldElement>(inp: Vec<T>, c: &Constants<T>, w: T) -> Vec<T> {
-    assert!(inp.len().is_power_of_two());
     let mut inp = inp.clone();
     let N = inp.len();
     let MOD = T::from(c.N);
     let ONE = T::from(1);
     let mut pre: Vec<T> = vec![ONE; N / 2];
     let CHUNK_COUNT = 128;
-    let chunk_count = T::from(CHUNK_COUNT);
+    let chunk_count = small(CHUNK_COUNT);
 
     pre.par_chunks_mut(CHUNK_COUNT)
         .enumerate()
-        .for_each(|(i, arr)| arr[0] = w.mod_exp(T::from(i) * chunk_count, MOD));
+        .for_each(|(i, arr)| arr[0] = w.mod_exp(small::<T>(i) * chunk_count, MOD));
     pre.par_chunks_mut(CHUNK_COUNT).for_each(|x| {
         (1..x.len()).for_each(|y| {
             let _x = x.to_vec();
@@ -123,17 +129,7 @@ fn fft<T: PolynomialFieldElement>(inp: Vec<T>, c: &Constants<T>, w: T) -> Vec<T>
                 .enumerate()
                 .for_each(|(idx, (lo, hi))| {
                     *hi = (*hi * pre[nchunks * idx]).rem(MOD);
-                    let neg = if *lo < *hi {
-                        (MOD + *lo) - *hi
-                    } else {
-                        *lo - *hi
-                    };
-                    *lo = if *lo + *hi >= MOD {
-                        (*lo + *hi) - MOD
-                    } else {
-                        *lo + *hi
-                    };
-                    *hi = neg;
+                    butterfly(lo, hi, MOD);
                 });
         });
         gap *= 2;
@@ -143,18 +139,17 @@ fn fft<T: PolynomialFieldElement>(inp: Vec<T>, c: &Constants<T>, w: T) -> Vec<T>
 
 #[cfg(not(feature = "parallel"))]
 fn fft<T: PolynomialFieldElement>(inp: Vec<T>, c: &Constants<T>, w: T) -> Vec<T> {
-    assert!(inp.len().is_power_of_two());
     let mut inp = inp.clone();
     let N = inp.len();
     let MOD = T::from(c.N);
     let ONE = T::from(1);
     let mut pre: Vec<T> = vec![ONE; N / 2];
     let CHUNK_COUNT = 128;
-    let chunk_count = T::from(CHUNK_COUNT);
+    let chunk_count = small(CHUNK_COUNT);
 
     pre.chunks_mut(CHUNK_COUNT)
         .enumerate()
-        .for_each(|(i, arr)| arr[0] = w.mod_exp(T::from(i) * chunk_count, MOD));
+        .for_each(|(i, arr)| arr[0] = w.mod_exp(small::<T>(i) * chunk_count, MOD));
     pre.chunks_mut(CHUNK_COUNT).for_each(|x| {
         (1..x.len()).for_each(|y| {
             let _x = x.to_vec();
@@ -174,17 +169,7 @@ fn fft<T: PolynomialFieldElement>(inp: Vec<T>, c: &Constants<T>, w: T) -> Vec<T>
                 .enumerate()
                 .for_each(|(idx, (lo, hi))| {
                     *hi = (*hi * pre[nchunks * idx]).rem(MOD);
-                    let neg = if *lo < *hi {
-                        (MOD + *lo) - *hi
-                    } else {
-                        *lo - *hi
-                    };
-                    *lo = if *lo + *hi >= MOD {
-                        (*lo + *hi) - MOD
-                    } else {
-                        *lo + *hi
-                    };
-                    *hi = neg;
+                    butterfly(lo, hi, MOD);
                 });
         });
         gap *= 2;
@@ -193,29 +178,244 @@ fn fft<T: PolynomialFieldElement>(inp: Vec<T>, c: &Constants<T>, w: T) -> Vec<T>
 }
 
 pub fn forward<T: PolynomialFieldElement>(inp: Vec<T>, c: &Constants<T>) -> Vec<T> {
-    fft(inp, c, c.w)
+    try_forward(inp, c).unwrap()
+}
+
+pub fn try_forward<T: PolynomialFieldElement>(
+    inp: Vec<T>,
+    c: &Constants<T>,
+) -> Result<Vec<T>, NttError> {
+    if !inp.len().is_power_of_two() {
+        return Err(NttError::NonPowerOfTwoLength(inp.len()));
+    }
+    Ok(fft(inp, c, c.w))
 }
 
-#[cfg(feature = "parallel")]
 pub fn inverse<T: PolynomialFieldElement>(inp: Vec<T>, c: &Constants<T>) -> Vec<T> {
-    let mut inv = T::from(inp.len());
-    let _ = inv.set_mod(c.N);
+    try_inverse(inp, c).unwrap()
+}
+
+#[cfg(feature = "parallel")]
+pub fn try_inverse<T: PolynomialFieldElement>(
+    inp: Vec<T>,
+    c: &Constants<T>,
+) -> Result<Vec<T>, NttError> {
+    if !inp.len().is_power_of_two() {
+        return Err(NttError::NonPowerOfTwoLength(inp.len()));
+    }
+    let mut inv = small::<T>(inp.len());
+    inv.set_mod(c.N)?;
     let inv = inv.invert();
     let w = c.w.invert();
     let mut res = fft(inp, c, w);
     res.par_iter_mut().for_each(|x| *x = (inv * (*x)).rem(c.N));
-    res
+    Ok(res)
 }
 
 #[cfg(not(feature = "parallel"))]
-pub fn inverse<T: PolynomialFieldElement>(inp: Vec<T>, c: &Constants<T>) -> Vec<T> {
-    let mut inv = T::from(inp.len());
-    let _ = inv.set_mod(c.N);
+pub fn try_inverse<T: PolynomialFieldElement>(
+    inp: Vec<T>,
+    c: &Constants<T>,
+) -> Result<Vec<T>, NttError> {
+    if !inp.len().is_power_of_two() {
+        return Err(NttError::NonPowerOfTwoLength(inp.len()));
+    }
+    let mut inv = small::<T>(inp.len());
+    inv.set_mod(c.N)?;
     let inv = inv.invert();
     let w = c.w.invert();
     let mut res = fft(inp, c, w);
     res.iter_mut().for_each(|x| *x = (inv * (*x)).rem(c.N));
-    res
+    Ok(res)
+}
+
+// the transforms of `a` and `b` multiplied pointwise and transformed back, at their length
+fn pointwise<T: PolynomialFieldElement>(
+    a: Vec<T>,
+    b: Vec<T>,
+    c: &Constants<T>,
+) -> Result<Vec<T>, NttError> {
+    let c = transform_constants(c, a.len()).ok_or(NttError::NoRootOfUnity)?;
+    let a = try_forward(a, &c)?;
+    let b = try_forward(b, &c)?;
+    let mul = a.iter().zip(b).map(|(&x, y)| (x * y).rem(c.N)).collect();
+    try_inverse(mul, &c)
+}
+
+fn reduced<T: PolynomialFieldElement>(a: &[T], q: T) -> Vec<T> {
+    a.iter().map(|x| x.rem(q)).collect()
+}
+
+// the `2n - 1` coefficients of the product, for lengths that are not a power of two
+fn acyclic<T: PolynomialFieldElement>(
+    a: &[T],
+    b: &[T],
+    c: &Constants<T>,
+) -> Result<Vec<T>, NttError> {
+    let n = a.len();
+    let size = (2 * n - 1).next_power_of_two();
+    let mut v1 = reduced(a, c.N);
+    v1.resize(size, T::from(0));
+    let mut v2 = reduced(b, c.N);
+    v2.resize(size, T::from(0));
+    let mut out = pointwise(v1, v2, c)?;
+    out.truncate(2 * n - 1);
+    Ok(out)
+}
+
+/// The product of `a` and `b` modulo `x^n - 1` and `N = c.N`, for `n` the length of both. A
+/// power-of-two `n` takes one transform of that length; other lengths are padded and folded.
+pub fn convolve_cyclic<T: PolynomialFieldElement>(a: &[T], b: &[T], c: &Constants<T>) -> Vec<T> {
+    try_convolve_cyclic(a, b, c).unwrap()
+}
+
+pub fn try_convolve_cyclic<T: PolynomialFieldElement>(
+    a: &[T],
+    b: &[T],
+    c: &Constants<T>,
+) -> Result<Vec<T>, NttError> {
+    let n = a.len();
+    if n != b.len() {
+        return Err(NttError::LengthMismatch(n, b.len()));
+    }
+    if n == 0 {
+        return Ok(vec![]);
+    }
+    if n.is_power_of_two() {
+        return pointwise(reduced(a, c.N), reduced(b, c.N), c);
+    }
+    let full = acyclic(a, b, c)?;
+    let (low, high) = full.split_at(n);
+    let mut out = low.to_vec();
+    out.iter_mut()
+        .zip(high)
+        .for_each(|(x, &y)| *x = (*x + y).rem(c.N));
+    Ok(out)
+}
+
+/// The product of `a` and `b` modulo `x^n + 1` and `N = c.N`, for `n` the length of both. A
+/// power-of-two `n` weights the inputs by powers of a root of order `2n`, so that one cyclic
+/// transform of length `n` suffices; other lengths are padded and folded.
+pub fn convolve_negacyclic<T: PolynomialFieldElement>(
+    a: &[T],
+    b: &[T],
+    c: &Constants<T>,
+) -> Vec<T> {
+    try_convolve_negacyclic(a, b, c).unwrap()
+}
+
+pub fn try_convolve_negacyclic<T: PolynomialFieldElement>(
+    a: &[T],
+    b: &[T],
+    c: &Constants<T>,
+) -> Result<Vec<T>, NttError> {
+    let n = a.len();
+    if n != b.len() {
+        return Err(NttError::LengthMismatch(n, b.len()));
+    }
+    if n == 0 {
+        return Ok(vec![]);
+    }
+    let q = c.N;
+    if n.is_power_of_two() {
+        let psi = transform_constants(c, 2 * n)
+            .ok_or(NttError::NoRootOfUnity)?
+            .w;
+        let weights = (0..n)
+            .scan(T::from(1), |acc, _| {
+                let x = *acc;
+                *acc = (*acc * psi).rem(q);
+                Some(x)
+            })
+            .collect_vec();
+        let weigh = |v: &[T]| {
+            v.iter()
+                .zip(&weights)
+                .map(|(x, &y)| (x.rem(q) * y).rem(q))
+                .collect_vec()
+        };
+        let c = Constants {
+            N: q,
+            w: (psi * psi).rem(q),
+        };
+        let out = pointwise(weigh(a), weigh(b), &c)?;
+        // `psi^-i = psi^(2n - i)`
+        let inv = psi.mod_exp(small(2 * n - 1), q);
+        return Ok(out
+            .iter()
+            .scan(T::from(1), |acc, &x| {
+                let y = (x * *acc).rem(q);
+                *acc = (*acc * inv).rem(q);
+                Some(y)
+            })
+            .collect());
+    }
+    let full = acyclic(a, b, c)?;
+    let (low, high) = full.split_at(n);
+    let mut out = low.to_vec();
+    out.iter_mut()
+        .zip(high)
+        .for_each(|(x, &y)| *x = ((*x + q) - y).rem(q));
+    Ok(out)
+}
+
+/// Coefficients `n - 1` through `m - 1` of the product of `a` and `b`, for lengths
+/// `1 <= n <= m`, from a single cyclic transform of length `m` rounded up to a power of two.
+/// The coefficients that wrap around all fall below `n - 1`, so the middle is left intact. This
+/// is the transpose of multiplication by `a`, and saves a third of the work in Newton iterations
+/// that only need the middle of a product.
+pub fn middle_product<T: PolynomialFieldElement>(a: &[T], b: &[T], c: &Constants<T>) -> Vec<T> {
+    try_middle_product(a, b, c).unwrap()
+}
+
+pub fn try_middle_product<T: PolynomialFieldElement>(
+    a: &[T],
+    b: &[T],
+    c: &Constants<T>,
+) -> Result<Vec<T>, NttError> {
+    let (n, m) = (a.len(), b.len());
+    if n == 0 || n > m {
+        return Err(NttError::LengthMismatch(n, m));
+    }
+    let size = m.next_power_of_two();
+    let c = transform_constants(c, size).ok_or(NttError::NoRootOfUnity)?;
+    let zero = T::from(0);
+
+    let mut v1 = a.to_vec();
+    v1.resize(size, zero);
+    let mut v2 = b.to_vec();
+    v2.resize(size, zero);
+    let a_forward = try_forward(v1, &c)?;
+    let b_forward = try_forward(v2, &c)?;
+
+    let mul = a_forward
+        .iter()
+        .zip(b_forward)
+        .map(|(&x, y)| (x * y).rem(c.N))
+        .collect();
+    let mut coef = try_inverse(mul, &c)?;
+    coef.truncate(m);
+    Ok(coef.split_off(n - 1))
+}
+
+/// The correlation `r[k] = a[k] b[0] + ... + a[k + m - 1] b[m - 1]` modulo `c.N` at each of the
+/// `n - m + 1` offsets where `b`, of length `m`, fits inside `a`, of length `n`. This is the
+/// middle product of `a` with `b` reversed, from one transform of length `n` rounded up to a
+/// power of two.
+pub fn cross_correlate<T: PolynomialFieldElement>(a: &[T], b: &[T], c: &Constants<T>) -> Vec<T> {
+    try_cross_correlate(a, b, c).unwrap()
+}
+
+pub fn try_cross_correlate<T: PolynomialFieldElement>(
+    a: &[T],
+    b: &[T],
+    c: &Constants<T>,
+) -> Result<Vec<T>, NttError> {
+    let b = b.iter().rev().map(|x| x.rem(c.N)).collect_vec();
+    try_middle_product(&b, &reduced(a, c.N), c).map_err(|e| match e {
+        NttError::LengthMismatch(m, n) => NttError::LengthMismatch(n, m),
+        e => e,
+    })
 }
 
 #[cfg(test)]
@@ -224,41 +424,141 @@ mod tests {
     use rayon::{iter::ParallelIterator, slice::ParallelSliceMut};
 
     use crate::{
-        ntt::{forward, inverse, working_modulus},
-        numbers::BigInt,
+        error::NttError,
+        ntt::{
+            convolve_cyclic, convolve_negacyclic, cross_correlate, forward, inverse,
+            try_convolve_cyclic, try_cross_correlate, try_forward, try_inverse,
+            try_working_modulus, working_modulus,
+        },
+        numbers::{BigInt256, BigInt64},
+        test_utils::{big, constants, P},
     };
 
     #[test]
     fn test_forward() {
         // let n = 1 << rand::thread_rng().gen::<u32>() % 8;
-        // let v: Vec<BigInt> = (0..n)
-        //     .map(|_| BigInt::from(rand::thread_rng().gen::<u32>() % (1 << 6)))
+        // let v: Vec<BigInt256> = (0..n)
+        //     .map(|_| BigInt256::from(rand::thread_rng().gen::<u32>() % (1 << 6)))
         //     .collect();
-        // let M = (*v.iter().max().unwrap() << 1) * BigInt::from(n) + 1;
+        // let M = (*v.iter().max().unwrap() << 1) * BigInt256::from(n) + 1;
         let n = 8;
-        let v: Vec<BigInt> = (0..n).map(|x| BigInt::from(x)).collect();
-        let M = BigInt::from(n) * BigInt::from(n) + 1;
-        let c = working_modulus(BigInt::from(n), BigInt::from(M));
+        let v: Vec<BigInt256> = (0..n).map(|x| BigInt256::from(x)).collect();
+        let M = BigInt256::from(n) * BigInt256::from(n) + 1;
+        let c = working_modulus(BigInt256::from(n), BigInt256::from(M));
         let forward = forward(v.clone(), &c);
         let inverse = inverse(forward, &c);
         v.iter().zip(inverse).for_each(|(&a, b)| assert_eq!(a, b));
     }
 
+    #[test]
+    fn test_errors() {
+        let c = working_modulus(BigInt256::from(8), BigInt256::from(65));
+        let v: Vec<BigInt256> = (0..6).map(|x| BigInt256::from(x)).collect();
+        assert_eq!(
+            try_forward(v.clone(), &c).unwrap_err(),
+            NttError::NonPowerOfTwoLength(6)
+        );
+        assert_eq!(
+            try_inverse(v, &c).unwrap_err(),
+            NttError::NonPowerOfTwoLength(6)
+        );
+        assert_eq!(
+            try_working_modulus(BigInt256::from(0), BigInt256::from(65)).unwrap_err(),
+            NttError::NoPrime
+        );
+        // a 33-bit modulus leaves no room for products in 64 bits
+        assert_eq!(
+            try_working_modulus(BigInt64::from(2), BigInt64::try_from(1_u64 << 32).unwrap())
+                .unwrap_err(),
+            NttError::Overflow
+        );
+        // 15 * 2^28 + 1 is composite and the search would run past 32 bits
+        assert_eq!(
+            try_working_modulus(BigInt64::from(1 << 28), BigInt64::from(15)).unwrap_err(),
+            NttError::Overflow
+        );
+    }
+
+    // `a b mod x^n - sign`, term by term
+    fn wrapped(a: &[u64], b: &[u64], sign: u64, p: u64) -> Vec<BigInt256> {
+        let n = a.len();
+        let mut out = vec![0; n];
+        (0..n).for_each(|i| {
+            (0..n).for_each(|j| {
+                let x = a[i] * b[j] % p;
+                let x = if i + j >= n { x * sign % p } else { x };
+                out[(i + j) % n] = (out[(i + j) % n] + x) % p;
+            })
+        });
+        out.iter().map(|&x| BigInt256::from(x)).collect()
+    }
+
+    #[test]
+    fn test_convolutions() {
+        let c = constants();
+        let p = P;
+        for n in [1, 6, 8, 100, 256] {
+            let a = (0..n)
+                .map(|_| rand::thread_rng().gen::<u64>() % p)
+                .collect::<Vec<_>>();
+            let b = (0..n)
+                .map(|_| rand::thread_rng().gen::<u64>() % p)
+                .collect::<Vec<_>>();
+            assert_eq!(
+                convolve_cyclic(&big(&a), &big(&b), &c),
+                wrapped(&a, &b, 1, p)
+            );
+            assert_eq!(
+                convolve_negacyclic(&big(&a), &big(&b), &c),
+                wrapped(&a, &b, p - 1, p)
+            );
+        }
+        assert_eq!(
+            try_convolve_cyclic(&big(&[1, 2]), &big(&[1]), &c).unwrap_err(),
+            NttError::LengthMismatch(2, 1)
+        );
+
+        // wildcard matching: with `0` as the wildcard, the pattern matches at `k` exactly when
+        // the sum of `p t (p - t)^2 = p^3 t - 2 p^2 t^2 + p t^3` over the window is zero
+        let code = |s: &str| {
+            s.bytes()
+                .map(|x| if x == b'?' { 0 } else { (x - b'a' + 1) as u64 })
+                .collect::<Vec<_>>()
+        };
+        let text = code("abcaxcabd");
+        let pattern = code("a?c");
+        let pow = |v: &[u64], k: u32| big(&v.iter().map(|&x| x.pow(k)).collect::<Vec<_>>());
+        let terms = [(1, 3, 1), (2, 2, p - 2), (3, 1, 1)].map(|(i, j, s)| {
+            cross_correlate(&pow(&text, i), &pow(&pattern, j), &c)
+                .iter()
+                .map(|&x| (x * BigInt256::from(s)).rem(c.N))
+                .collect::<Vec<_>>()
+        });
+        let matches = (0..terms[0].len())
+            .filter(|&k| (terms[0][k] + terms[1][k] + terms[2][k]).rem(c.N) == BigInt256::from(0))
+            .collect::<Vec<_>>();
+        assert_eq!(matches, vec![0, 3]);
+        assert_eq!(
+            try_cross_correlate(&big(&[1, 2]), &big(&[1, 2, 3]), &c).unwrap_err(),
+            NttError::LengthMismatch(2, 3)
+        );
+    }
+
     #[test]
     fn test_roots_of_unity() {
         let N = 10;
-        let ONE = BigInt::from(1);
-        let mut pre: Vec<BigInt> = vec![ONE; N / 2];
+        let ONE = BigInt256::from(1);
+        let mut pre: Vec<BigInt256> = vec![ONE; N / 2];
         let mut pre2 = pre.clone();
         let CHUNK_COUNT = 128;
-        let MOD = BigInt::from(10);
-        let chunk_count = BigInt::from(CHUNK_COUNT);
-        let w = BigInt::from(2);
+        let MOD = BigInt256::from(10);
+        let chunk_count = BigInt256::from(CHUNK_COUNT);
+        let w = BigInt256::from(2);
 
         (1..N / 2).for_each(|i| pre[i] = (pre[i - 1] * w).rem(MOD));
 
         (1..N / (2 * CHUNK_COUNT))
-            .for_each(|i| pre2[i * CHUNK_COUNT] = w.mod_exp(BigInt::from(i) * chunk_count, MOD));
+            .for_each(|i| pre2[i * CHUNK_COUNT] = w.mod_exp(BigInt256::from(i) * chunk_count, MOD));
         pre2.par_chunks_mut(CHUNK_COUNT).for_each(|x| {
             (1..x.len()).for_each(|y| {
                 let _x = x.to_vec();
@@ -1,24 +1,32 @@
 use std::{
     cmp::Ordering,
-    fmt::Display,
-    num::NonZeroU128,
+    fmt::{Display, LowerHex, UpperHex},
+    hash::{Hash, Hasher},
     ops::{
         Add, AddAssign, BitAnd, BitOr, Div, DivAssign, Mul, MulAssign, Neg, Shl, ShlAssign, Shr,
         ShrAssign, Sub, SubAssign,
     },
+    str::FromStr,
 };
 
 use crypto_bigint::{
     modular::{
+        constant_mod::ResidueParams,
         runtime_mod::{DynResidue, DynResidueParams},
         Retrieve,
     },
-    Invert, NonZero, Uint, U128, U256,
+    CheckedAdd, CheckedMul, Invert, Limb, NonZero, Uint, U128,
 };
 use itertools::Itertools;
 use rand::{thread_rng, Error, Rng};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+#[cfg(feature = "serde")]
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+#[cfg(feature = "constant-time")]
+use subtle::{Choice, ConditionallySelectable};
 
-use crate::polynomial::PolynomialFieldElement;
+use crate::{error::NttError, polynomial::PolynomialFieldElement};
 
 pub enum BigIntType {
     U16(u16),
@@ -29,50 +37,117 @@ pub enum BigIntType {
 
 pub trait NttFieldElement {
     // all operations should be under the modular group `M`
-    fn set_mod(&mut self, M: Self) -> Result<(), String>;
+    fn set_mod(&mut self, M: Self) -> Result<(), NttError>;
     fn rem(&self, M: Self) -> Self;
     fn pow(&self, n: u128) -> Self;
     fn mod_exp(&self, exp: Self, M: Self) -> Self;
     fn is_even(&self) -> bool;
     fn is_zero(&self) -> bool;
-    fn to_bigint(&self) -> BigInt;
+    // little-endian 64-bit words of the value
+    fn to_words(&self) -> Vec<u64>;
+
+    /// The low 256 bits of the value, under the default 256-bit modulus.
+    #[deprecated(note = "use `to_words`, which covers every width")]
+    fn to_bigint(&self) -> BigInt {
+        let base = BigInt::from(1_u128 << 64);
+        self.to_words()
+            .iter()
+            .take(4)
+            .rev()
+            .fold(BigInt::from(0), |acc, &w| acc * base + BigInt::from(w))
+    }
 }
 
-#[derive(Debug, Clone, Copy)]
-pub struct BigInt {
-    pub v: DynResidue<4>,
+const P256_ORDER: &str = "ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551";
+
+// The modulus `2^(64 * LIMBS) - 1`. Its Montgomery constants are all 1, since `2^(64 * LIMBS)` is
+// 1 modulo it and its lowest limb is -1, whereas `DynResidueParams::new` would need a wide
+// division to find them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct AllOnes<const LIMBS: usize>;
+
+impl<const LIMBS: usize> ResidueParams<LIMBS> for AllOnes<LIMBS> {
+    const LIMBS: usize = LIMBS;
+    const MODULUS: Uint<LIMBS> = Uint::MAX;
+    const R: Uint<LIMBS> = Uint::ONE;
+    const R2: Uint<LIMBS> = Uint::ONE;
+    const R3: Uint<LIMBS> = Uint::ONE;
+    const MOD_NEG_INV: Limb = Limb::ONE;
 }
 
-impl BigInt {
+/// An integer of `64 * LIMBS` bits, held as a residue modulo its current modulus. Products of
+/// two coefficients must fit, so NTT moduli must stay below `2^(32 * LIMBS)`: 32-bit moduli for
+/// `BigInt64`, 64-bit ones for `BigInt128`, and so on. `try_working_modulus` rejects wider ones.
+#[derive(Debug, Clone, Copy)]
+pub struct BigInt<const LIMBS: usize = 4> {
+    pub v: DynResidue<LIMBS>,
+}
+
+/// Values from `2^64 - 1` up do not fit, so `u64`, `usize`, `u128` and `i128` convert with
+/// `TryFrom` rather than `From`.
+pub type BigInt64 = BigInt<1>;
+/// `u128` converts with `TryFrom`, since `2^128 - 1` does not fit.
+pub type BigInt128 = BigInt<2>;
+pub type BigInt256 = BigInt<4>;
+pub type BigInt512 = BigInt<8>;
+pub type BigInt1024 = BigInt<16>;
+pub type BigInt2048 = BigInt<32>;
+pub type BigInt4096 = BigInt<64>;
+
+impl<const LIMBS: usize> BigInt<LIMBS> {
+    // values start out modulo the P-256 group order at 256 bits, and modulo `2^(64 * LIMBS) - 1`
+    // at every other width
+    const DEFAULT_MODULUS: Uint<LIMBS> = if LIMBS == 4 {
+        Uint::from_be_hex(P256_ORDER)
+    } else {
+        Uint::MAX
+    };
+
+    // known at compile time, so building a value takes no division
+    const DEFAULT_PARAMS: DynResidueParams<LIMBS> = if LIMBS == 4 {
+        DynResidueParams::new(&Self::DEFAULT_MODULUS)
+    } else {
+        DynResidueParams::from_residue_params::<AllOnes<LIMBS>>()
+    };
+
+    /// Panics where `try_new` errors, rather than wrapping around the default modulus.
     pub fn new(_v: BigIntType) -> Self {
-        let params = DynResidueParams::new(&U256::from_be_hex(
-            "ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551",
-        ));
-        Self {
-            v: match _v {
-                BigIntType::U16(x) => DynResidue::new(&U256::from(x), params),
-                BigIntType::U32(x) => DynResidue::new(&U256::from(x), params),
-                BigIntType::U64(x) => DynResidue::new(&U256::from(x), params),
-                BigIntType::U128(x) => DynResidue::new(&U256::from(x), params),
-                _ => panic!("received invalid `BigIntType`"),
-            },
+        Self::try_new(_v).expect("value does not fit below the default modulus")
+    }
+
+    /// `_v` under the default modulus, or `Overflow` if it is not below that modulus. This only
+    /// happens for the widest values at 64 and 128 bits.
+    pub fn try_new(_v: BigIntType) -> Result<Self, NttError> {
+        let x = match _v {
+            BigIntType::U16(x) => Uint::from(x),
+            BigIntType::U32(x) => Uint::from(x),
+            BigIntType::U64(x) => Uint::from(x),
+            BigIntType::U128(x) if LIMBS >= 2 => Uint::from_u128(x),
+            BigIntType::U128(x) => Uint::from(u64::try_from(x).map_err(|_| NttError::Overflow)?),
+        };
+        if x >= Self::DEFAULT_MODULUS {
+            return Err(NttError::Overflow);
         }
+        Ok(Self {
+            v: DynResidue::new(&x, Self::DEFAULT_PARAMS),
+        })
     }
 
-    pub fn set_mod(&mut self, M: BigInt) -> Result<(), String> {
+    pub fn set_mod(&mut self, M: Self) -> Result<(), NttError> {
         if M.is_even() {
-            return Err("modulus must be odd".to_string());
+            return Err(NttError::EvenModulus);
         }
-        let params = DynResidueParams::new(&(U256::from(M.v.retrieve())));
+        let params = DynResidueParams::new(&M.v.retrieve());
         self.v = DynResidue::new(&self.v.retrieve(), params);
         Ok(())
     }
 
-    pub fn set_mod_from_residue(&mut self, params: DynResidueParams<4>) {
+    pub fn set_mod_from_residue(&mut self, params: DynResidueParams<LIMBS>) {
         self.v = DynResidue::new(&self.v.retrieve(), params);
     }
 
-    pub fn rem(&self, M: BigInt) -> BigInt {
+    #[cfg(not(feature = "constant-time"))]
+    pub fn rem(&self, M: Self) -> Self {
         let mut res = self.clone();
         if res < M {
             return res;
@@ -84,26 +159,38 @@ impl BigInt {
         res
     }
 
-    pub fn params(&self) -> DynResidueParams<4> {
+    /// Always divides, so the timing depends on `M` but not on `self`.
+    #[cfg(feature = "constant-time")]
+    pub fn rem(&self, M: Self) -> Self {
+        let mut res = *self;
+        res.v = DynResidue::new(
+            &res.v.retrieve().rem(&NonZero::from_uint(M.v.retrieve())),
+            res.params(),
+        );
+        res
+    }
+
+    pub fn params(&self) -> DynResidueParams<LIMBS> {
         *self.v.params()
     }
 
-    pub fn pow(&self, n: u128) -> BigInt {
-        BigInt {
-            v: self.v.pow(&Uint::<4>::from_u128(n)),
+    pub fn pow(&self, n: u128) -> Self {
+        Self {
+            v: self.v.pow(&U128::from_u128(n)),
         }
     }
 
-    pub fn mod_exp(&self, exp: BigInt, M: BigInt) -> BigInt {
-        let mut res: BigInt = if !exp.is_even() {
+    #[cfg(not(feature = "constant-time"))]
+    pub fn mod_exp(&self, exp: Self, M: Self) -> Self {
+        let mut res: Self = if !exp.is_even() {
             self.clone()
         } else {
-            BigInt::from(1)
+            Self::from(1)
         };
         let mut b = self.clone();
         let mut e = exp.clone();
-        res.set_mod(M);
         b.set_mod(M);
+        res.set_mod_from_residue(b.params());
         while e > 0 {
             e >>= 1;
             b = b * b;
@@ -120,9 +207,42 @@ impl BigInt {
         res
     }
 
-    pub fn random() -> BigInt {
-        let x = rand::thread_rng().gen::<u128>();
-        BigInt::new(BigIntType::U128(x))
+    /// Montgomery ladder over all `64 * LIMBS` bits of `exp`: every bit costs one multiplication
+    /// and one squaring, and the operands are swapped with masks instead of branches.
+    #[cfg(feature = "constant-time")]
+    pub fn mod_exp(&self, exp: Self, M: Self) -> Self {
+        // the modulus is public, so only the reduction strategy may depend on it
+        let reduce = |x: Self| if M.is_even() { x.rem(M) } else { x };
+        let mut r1 = *self;
+        let _ = r1.set_mod(M);
+        r1 = reduce(r1);
+        let mut r0 = Self::from(1);
+        r0.set_mod_from_residue(r1.params());
+
+        let e = exp.v.retrieve();
+        let words = e.as_words();
+        for i in (0..Uint::<LIMBS>::BITS).rev() {
+            let bit = Choice::from(((words[i / 64] >> (i % 64)) & 1) as u8);
+            DynResidue::conditional_swap(&mut r0.v, &mut r1.v, bit);
+            r1 = reduce(r0 * r1);
+            r0 = reduce(r0 * r0);
+            DynResidue::conditional_swap(&mut r0.v, &mut r1.v, bit);
+        }
+        r0
+    }
+
+    pub fn random() -> Self {
+        let mut rng = rand::thread_rng();
+        loop {
+            let x = if LIMBS == 1 {
+                BigIntType::U64(rng.gen())
+            } else {
+                BigIntType::U128(rng.gen())
+            };
+            if let Ok(x) = Self::try_new(x) {
+                return x;
+            }
+        }
     }
 
     pub fn is_zero(&self) -> bool {
@@ -134,26 +254,61 @@ impl BigInt {
         !is_odd
     }
 
-    pub fn to_u32(&self) -> Result<u32, String> {
+    pub fn to_u32(&self) -> Result<u32, NttError> {
         let ret = self.v.retrieve().as_words()[0] as u32;
-        if BigInt::from(ret) != *self {
-            return Err(format!("Overflow error -- {} exceeds u32 size limits", self).to_string());
+        if Self::from(ret) != *self {
+            return Err(NttError::Overflow);
         }
         Ok(ret)
     }
+
+    /// The representative of `self mod q` in `(-q/2, q/2]`.
+    pub fn to_signed(&self, q: Self) -> Result<i128, NttError> {
+        let x = self.rem(q);
+        let (abs, negative) = if x > q >> 1 {
+            (q - x, true)
+        } else {
+            (x, false)
+        };
+        let words = abs.to_words();
+        if words.iter().skip(2).any(|&w| w != 0) {
+            return Err(NttError::Overflow);
+        }
+        let abs = words[0] as u128 | (words.get(1).copied().unwrap_or(0) as u128) << 64;
+        let ret = if negative {
+            0_i128.checked_sub_unsigned(abs)
+        } else {
+            i128::try_from(abs).ok()
+        };
+        ret.ok_or(NttError::Overflow)
+    }
+
+    /// `x` as an element of `Z_q`, so a negative `-x` maps to `q - x`. The signed `From` impls
+    /// reduce modulo the default modulus instead.
+    pub fn from_signed(x: i128, q: Self) -> Self
+    where
+        Self: PolynomialFieldElement,
+    {
+        <Self as PolynomialFieldElement>::from_signed(x, q)
+    }
+
+    pub fn to_centered_i64(&self, q: Self) -> Result<i64, NttError> {
+        i64::try_from(self.to_signed(q)?).map_err(|_| NttError::Overflow)
+    }
 }
 
-impl NttFieldElement for BigInt {
-    fn set_mod(&mut self, M: Self) -> Result<(), String> {
+impl<const LIMBS: usize> NttFieldElement for BigInt<LIMBS> {
+    fn set_mod(&mut self, M: Self) -> Result<(), NttError> {
         if M.is_even() {
-            return Err("modulus must be odd".to_string());
+            return Err(NttError::EvenModulus);
         }
-        let params = DynResidueParams::new(&(U256::from(M.v.retrieve())));
+        let params = DynResidueParams::new(&M.v.retrieve());
         self.v = DynResidue::new(&self.v.retrieve(), params);
         Ok(())
     }
 
-    fn rem(&self, M: Self) -> BigInt {
+    #[cfg(not(feature = "constant-time"))]
+    fn rem(&self, M: Self) -> Self {
         let mut res = self.clone();
         if res < M {
             return res;
@@ -165,22 +320,23 @@ impl NttFieldElement for BigInt {
         res
     }
 
-    fn pow(&self, n: u128) -> BigInt {
-        BigInt {
-            v: self.v.pow(&Uint::<4>::from_u128(n)),
+    fn pow(&self, n: u128) -> Self {
+        Self {
+            v: self.v.pow(&U128::from_u128(n)),
         }
     }
 
-    fn mod_exp(&self, exp: BigInt, M: BigInt) -> BigInt {
-        let mut res: BigInt = if !exp.is_even() {
+    #[cfg(not(feature = "constant-time"))]
+    fn mod_exp(&self, exp: Self, M: Self) -> Self {
+        let mut res: Self = if !exp.is_even() {
             self.clone()
         } else {
-            BigInt::from(1)
+            Self::from(1)
         };
         let mut b = self.clone();
         let mut e = exp.clone();
-        res.set_mod(M);
         b.set_mod(M);
+        res.set_mod_from_residue(b.params());
         while e > 0 {
             e >>= 1;
             b = b * b;
@@ -197,6 +353,16 @@ impl NttFieldElement for BigInt {
         res
     }
 
+    #[cfg(feature = "constant-time")]
+    fn rem(&self, M: Self) -> Self {
+        BigInt::rem(self, M)
+    }
+
+    #[cfg(feature = "constant-time")]
+    fn mod_exp(&self, exp: Self, M: Self) -> Self {
+        BigInt::mod_exp(self, exp, M)
+    }
+
     fn is_zero(&self) -> bool {
         self.v.retrieve().bits() == 0
     }
@@ -206,49 +372,100 @@ impl NttFieldElement for BigInt {
         !is_odd
     }
 
-    fn to_bigint(&self) -> BigInt {
-        *self
+    fn to_words(&self) -> Vec<u64> {
+        self.v.retrieve().to_words().to_vec()
     }
 }
 
-impl From<u16> for BigInt {
+impl<const LIMBS: usize> From<u16> for BigInt<LIMBS> {
     fn from(value: u16) -> Self {
-        BigInt::new(BigIntType::U16(value))
+        Self::new(BigIntType::U16(value))
     }
 }
 
-impl From<i32> for BigInt {
-    fn from(value: i32) -> Self {
-        BigInt::new(BigIntType::U32(value as u32))
+impl<const LIMBS: usize> From<u32> for BigInt<LIMBS> {
+    fn from(value: u32) -> Self {
+        Self::new(BigIntType::U32(value))
     }
 }
 
-impl From<usize> for BigInt {
-    fn from(value: usize) -> Self {
-        BigInt::new(BigIntType::U64(value as u64))
+// a negative `-x` maps to `-BigInt::from(x)`, the default modulus less `x`
+impl<const LIMBS: usize> From<i32> for BigInt<LIMBS> {
+    fn from(value: i32) -> Self {
+        let x = Self::from(value.unsigned_abs());
+        if value < 0 {
+            -x
+        } else {
+            x
+        }
     }
 }
 
-impl From<u32> for BigInt {
-    fn from(value: u32) -> Self {
-        BigInt::new(BigIntType::U32(value))
+impl<const LIMBS: usize> From<i64> for BigInt<LIMBS> {
+    fn from(value: i64) -> Self {
+        Self::try_from_i128(value as i128).expect("|i64| is below every default modulus")
     }
 }
 
-impl From<u64> for BigInt {
-    fn from(value: u64) -> Self {
-        BigInt::new(BigIntType::U64(value))
+impl<const LIMBS: usize> BigInt<LIMBS> {
+    fn try_from_u64(value: u64) -> Result<Self, NttError> {
+        Self::try_new(BigIntType::U64(value))
+    }
+
+    fn try_from_usize(value: usize) -> Result<Self, NttError> {
+        Self::try_new(BigIntType::U64(value as u64))
     }
-}
 
-impl From<u128> for BigInt {
-    fn from(value: u128) -> Self {
-        BigInt::new(BigIntType::U128(value))
+    fn try_from_u128(value: u128) -> Result<Self, NttError> {
+        Self::try_new(BigIntType::U128(value))
     }
+
+    fn try_from_i128(value: i128) -> Result<Self, NttError> {
+        let x = Self::try_from_u128(value.unsigned_abs())?;
+        Ok(if value < 0 { -x } else { x })
+    }
+
+    // compares without building `other` under the default modulus, which may not hold it
+    fn cmp_u128(&self, other: u128) -> Ordering {
+        let words = self.v.retrieve().to_words();
+        if words.iter().skip(2).any(|&w| w != 0) {
+            return Ordering::Greater;
+        }
+        let high = words.get(1).map_or(0, |&w| w as u128);
+        ((high << 64) | words[0] as u128).cmp(&other)
+    }
+}
+
+// `From` at the widths whose default modulus lies above every value of `$t`, and `TryFrom`,
+// failing with `Overflow`, at the narrower ones
+macro_rules! impl_from_wide {
+    ($t:ty, $try_from:ident, [$($wide:literal),*], [$($narrow:literal),*]) => {
+        $(
+            impl From<$t> for BigInt<$wide> {
+                fn from(value: $t) -> Self {
+                    Self::$try_from(value).expect("every value fits at this width")
+                }
+            }
+        )*
+        $(
+            impl TryFrom<$t> for BigInt<$narrow> {
+                type Error = NttError;
+
+                fn try_from(value: $t) -> Result<Self, NttError> {
+                    Self::$try_from(value)
+                }
+            }
+        )*
+    };
 }
 
-impl Add for BigInt {
-    type Output = BigInt;
+impl_from_wide!(u64, try_from_u64, [2, 4, 8, 16, 32, 64], [1]);
+impl_from_wide!(usize, try_from_usize, [2, 4, 8, 16, 32, 64], [1]);
+impl_from_wide!(i128, try_from_i128, [2, 4, 8, 16, 32, 64], [1]);
+impl_from_wide!(u128, try_from_u128, [4, 8, 16, 32, 64], [1, 2]);
+
+impl<const LIMBS: usize> Add for BigInt<LIMBS> {
+    type Output = Self;
 
     fn add(self, rhs: Self) -> Self::Output {
         if rhs.v.params() != self.v.params() {
@@ -260,94 +477,94 @@ impl Add for BigInt {
     }
 }
 
-impl Add<u16> for BigInt {
-    type Output = BigInt;
+impl<const LIMBS: usize> Add<u16> for BigInt<LIMBS> {
+    type Output = Self;
 
     fn add(self, rhs: u16) -> Self::Output {
         Self {
-            v: self.v + BigInt::from(rhs).v,
+            v: self.v + Self::from(rhs).v,
         }
     }
 }
 
-impl Add<i32> for BigInt {
-    type Output = BigInt;
+impl<const LIMBS: usize> Add<i32> for BigInt<LIMBS> {
+    type Output = Self;
 
     fn add(self, rhs: i32) -> Self::Output {
         Self {
-            v: self.v + BigInt::from(rhs).v,
+            v: self.v + Self::from(rhs).v,
         }
     }
 }
 
-impl Add<u32> for BigInt {
-    type Output = BigInt;
+impl<const LIMBS: usize> Add<u32> for BigInt<LIMBS> {
+    type Output = Self;
 
     fn add(self, rhs: u32) -> Self::Output {
         Self {
-            v: self.v + BigInt::from(rhs).v,
+            v: self.v + Self::from(rhs).v,
         }
     }
 }
 
-impl Add<u64> for BigInt {
-    type Output = BigInt;
+impl<const LIMBS: usize> Add<u64> for BigInt<LIMBS> {
+    type Output = Self;
 
     fn add(self, rhs: u64) -> Self::Output {
         Self {
-            v: self.v + BigInt::from(rhs).v,
+            v: self.v + Self::new(BigIntType::U64(rhs)).v,
         }
     }
 }
 
-impl Add<u128> for BigInt {
-    type Output = BigInt;
+impl<const LIMBS: usize> Add<u128> for BigInt<LIMBS> {
+    type Output = Self;
 
     fn add(self, rhs: u128) -> Self::Output {
         Self {
-            v: self.v + BigInt::from(rhs).v,
+            v: self.v + Self::new(BigIntType::U128(rhs)).v,
         }
     }
 }
 
-impl AddAssign for BigInt {
+impl<const LIMBS: usize> AddAssign for BigInt<LIMBS> {
     fn add_assign(&mut self, rhs: Self) {
         *self = *self + rhs
     }
 }
 
-impl AddAssign<u16> for BigInt {
+impl<const LIMBS: usize> AddAssign<u16> for BigInt<LIMBS> {
     fn add_assign(&mut self, rhs: u16) {
-        *self = *self + BigInt::from(rhs);
+        *self = *self + Self::from(rhs);
     }
 }
 
-impl AddAssign<u32> for BigInt {
+impl<const LIMBS: usize> AddAssign<u32> for BigInt<LIMBS> {
     fn add_assign(&mut self, rhs: u32) {
-        *self = *self + BigInt::from(rhs);
+        *self = *self + Self::from(rhs);
     }
 }
 
-impl AddAssign<i32> for BigInt {
+impl<const LIMBS: usize> AddAssign<i32> for BigInt<LIMBS> {
     fn add_assign(&mut self, rhs: i32) {
-        *self = *self + BigInt::from(rhs);
+        *self = *self + Self::from(rhs);
     }
 }
 
-impl AddAssign<u64> for BigInt {
+impl<const LIMBS: usize> AddAssign<u64> for BigInt<LIMBS> {
     fn add_assign(&mut self, rhs: u64) {
-        *self = *self + BigInt::from(rhs);
+        *self = *self + Self::new(BigIntType::U64(rhs));
     }
 }
 
-impl AddAssign<u128> for BigInt {
+impl<const LIMBS: usize> AddAssign<u128> for BigInt<LIMBS> {
     fn add_assign(&mut self, rhs: u128) {
-        *self = *self + BigInt::from(rhs);
+        *self = *self + Self::new(BigIntType::U128(rhs));
     }
 }
 
-impl Sub for BigInt {
-    type Output = BigInt;
+impl<const LIMBS: usize> Sub for BigInt<LIMBS> {
+    type Output = Self;
 
     fn sub(self, rhs: Self) -> Self::Output {
         if rhs.v.params() != self.v.params() {
@@ -359,100 +576,100 @@ impl Sub for BigInt {
     }
 }
 
-impl Sub<usize> for BigInt {
-    type Output = BigInt;
+impl<const LIMBS: usize> Sub<usize> for BigInt<LIMBS> {
+    type Output = Self;
 
     fn sub(self, rhs: usize) -> Self::Output {
-        self - BigInt::from(rhs)
+        self - Self::new(BigIntType::U64(rhs as u64))
     }
 }
 
-impl Sub<u16> for BigInt {
-    type Output = BigInt;
+impl<const LIMBS: usize> Sub<u16> for BigInt<LIMBS> {
+    type Output = Self;
 
     fn sub(self, rhs: u16) -> Self::Output {
-        self - BigInt::from(rhs)
+        self - Self::new(BigIntType::U64(rhs as u64))
     }
 }
 
-impl Sub<u32> for BigInt {
-    type Output = BigInt;
+impl<const LIMBS: usize> Sub<u32> for BigInt<LIMBS> {
+    type Output = Self;
 
     fn sub(self, rhs: u32) -> Self::Output {
-        self - BigInt::from(rhs)
+        self - Self::new(BigIntType::U64(rhs as u64))
     }
 }
 
-impl Sub<i32> for BigInt {
-    type Output = BigInt;
+impl<const LIMBS: usize> Sub<i32> for BigInt<LIMBS> {
+    type Output = Self;
 
     fn sub(self, rhs: i32) -> Self::Output {
-        self - BigInt::from(rhs)
+        self - Self::new(BigIntType::U64(rhs as u64))
     }
 }
 
-impl Sub<u64> for BigInt {
-    type Output = BigInt;
+impl<const LIMBS: usize> Sub<u64> for BigInt<LIMBS> {
+    type Output = Self;
 
     fn sub(self, rhs: u64) -> Self::Output {
-        self - BigInt::from(rhs)
+        self - Self::new(BigIntType::U64(rhs))
     }
 }
 
-impl Sub<u128> for BigInt {
-    type Output = BigInt;
+impl<const LIMBS: usize> Sub<u128> for BigInt<LIMBS> {
+    type Output = Self;
 
     fn sub(self, rhs: u128) -> Self::Output {
-        self - BigInt::from(rhs)
+        self - Self::new(BigIntType::U128(rhs))
     }
 }
 
-impl SubAssign for BigInt {
+impl<const LIMBS: usize> SubAssign for BigInt<LIMBS> {
     fn sub_assign(&mut self, rhs: Self) {
         *self = *self - rhs
     }
 }
 
-impl SubAssign<u16> for BigInt {
+impl<const LIMBS: usize> SubAssign<u16> for BigInt<LIMBS> {
     fn sub_assign(&mut self, rhs: u16) {
-        *self = *self - BigInt::from(rhs);
+        *self = *self - Self::from(rhs);
     }
 }
 
-impl SubAssign<u32> for BigInt {
+impl<const LIMBS: usize> SubAssign<u32> for BigInt<LIMBS> {
     fn sub_assign(&mut self, rhs: u32) {
-        *self = *self - BigInt::from(rhs);
+        *self = *self - Self::from(rhs);
     }
 }
 
-impl SubAssign<i32> for BigInt {
+impl<const LIMBS: usize> SubAssign<i32> for BigInt<LIMBS> {
     fn sub_assign(&mut self, rhs: i32) {
-        *self = *self - BigInt::from(rhs);
+        *self = *self - Self::from(rhs);
     }
 }
 
-impl SubAssign<u64> for BigInt {
+impl<const LIMBS: usize> SubAssign<u64> for BigInt<LIMBS> {
     fn sub_assign(&mut self, rhs: u64) {
-        *self = *self - BigInt::from(rhs);
+        *self = *self - Self::new(BigIntType::U64(rhs));
     }
 }
 
-impl SubAssign<u128> for BigInt {
+impl<const LIMBS: usize> SubAssign<u128> for BigInt<LIMBS> {
     fn sub_assign(&mut self, rhs: u128) {
-        *self = *self - BigInt::from(rhs);
+        *self = *self - Self::new(BigIntType::U128(rhs));
     }
 }
 
-impl Neg for BigInt {
-    type Output = BigInt;
+impl<const LIMBS: usize> Neg for BigInt<LIMBS> {
+    type Output = Self;
 
     fn neg(self) -> Self::Output {
         Self { v: self.v.neg() }
     }
 }
 
-impl Mul for BigInt {
-    type Output = BigInt;
+impl<const LIMBS: usize> Mul for BigInt<LIMBS> {
+    type Output = Self;
 
     fn mul(self, rhs: Self) -> Self::Output {
         if rhs.v.params() != self.v.params() {
@@ -464,17 +681,17 @@ impl Mul for BigInt {
     }
 }
 
-impl MulAssign for BigInt {
+impl<const LIMBS: usize> MulAssign for BigInt<LIMBS> {
     fn mul_assign(&mut self, rhs: Self) {
         *self = *self * rhs
     }
 }
 
-impl Div for BigInt {
-    type Output = BigInt;
+impl<const LIMBS: usize> Div for BigInt<LIMBS> {
+    type Output = Self;
 
     fn div(self, rhs: Self) -> Self::Output {
-        BigInt {
+        Self {
             v: DynResidue::new(
                 &(self
                     .v
@@ -487,316 +704,535 @@ impl Div for BigInt {
     }
 }
 
-impl Invert for BigInt {
-    type Output = BigInt;
+impl<const LIMBS: usize> Invert for BigInt<LIMBS> {
+    type Output = Self;
 
     fn invert(&self) -> Self::Output {
-        BigInt {
+        Self {
             v: self.v.invert().0,
         }
     }
 }
 
-impl DivAssign for BigInt {
+impl<const LIMBS: usize> DivAssign for BigInt<LIMBS> {
     fn div_assign(&mut self, rhs: Self) {
         *self = *self / rhs;
     }
 }
 
-impl Eq for BigInt {}
+impl<const LIMBS: usize> Eq for BigInt<LIMBS> {}
+
+// hashes the value alone, like `eq`, so equal values under different moduli agree
+impl<const LIMBS: usize> Hash for BigInt<LIMBS> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.v.retrieve().hash(state);
+    }
+}
 
-impl Ord for BigInt {
+impl<const LIMBS: usize> Ord for BigInt<LIMBS> {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        let half = self
-            .params()
-            .modulus()
-            .div(NonZero::from(NonZeroU128::new(2).unwrap()));
+        let half = self.params().modulus().shr_vartime(1);
         (self.v - other.v).retrieve().cmp(&half)
     }
 }
 
-impl PartialEq for BigInt {
+impl<const LIMBS: usize> PartialEq for BigInt<LIMBS> {
     fn eq(&self, other: &Self) -> bool {
         self.v.retrieve() == other.v.retrieve()
     }
 }
 
-impl PartialEq<u16> for BigInt {
+impl<const LIMBS: usize> PartialEq<u16> for BigInt<LIMBS> {
     fn eq(&self, other: &u16) -> bool {
-        self.v.retrieve() == BigInt::from(*other).v.retrieve()
+        self.v.retrieve() == Self::from(*other).v.retrieve()
     }
 }
 
-impl PartialEq<i32> for BigInt {
+impl<const LIMBS: usize> PartialEq<i32> for BigInt<LIMBS> {
     fn eq(&self, other: &i32) -> bool {
-        self.v.retrieve() == BigInt::from(*other).v.retrieve()
+        self.v.retrieve() == Self::from(*other).v.retrieve()
     }
 }
 
-impl PartialEq<u32> for BigInt {
+impl<const LIMBS: usize> PartialEq<u32> for BigInt<LIMBS> {
     fn eq(&self, other: &u32) -> bool {
-        self.v.retrieve() == BigInt::from(*other).v.retrieve()
+        self.v.retrieve() == Self::from(*other).v.retrieve()
     }
 }
 
-impl PartialEq<u64> for BigInt {
+impl<const LIMBS: usize> PartialEq<u64> for BigInt<LIMBS> {
     fn eq(&self, other: &u64) -> bool {
-        self.v.retrieve() == BigInt::from(*other).v.retrieve()
+        self.cmp_u128(*other as u128).is_eq()
     }
 }
 
-impl PartialEq<u128> for BigInt {
+impl<const LIMBS: usize> PartialEq<u128> for BigInt<LIMBS> {
     fn eq(&self, other: &u128) -> bool {
-        self.v.retrieve() == BigInt::from(*other).v.retrieve()
+        self.cmp_u128(*other).is_eq()
     }
 }
 
-impl PartialOrd for BigInt {
+impl<const LIMBS: usize> PartialOrd for BigInt<LIMBS> {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         (self.v.retrieve()).partial_cmp(&(other.v.retrieve()))
     }
 }
 
-impl PartialOrd<u16> for BigInt {
+impl<const LIMBS: usize> PartialOrd<u16> for BigInt<LIMBS> {
     fn partial_cmp(&self, other: &u16) -> Option<std::cmp::Ordering> {
-        (self.v.retrieve()).partial_cmp(&BigInt::from(*other).v.retrieve())
+        (self.v.retrieve()).partial_cmp(&Self::from(*other).v.retrieve())
     }
 }
 
-impl PartialOrd<i32> for BigInt {
+impl<const LIMBS: usize> PartialOrd<i32> for BigInt<LIMBS> {
     fn partial_cmp(&self, other: &i32) -> Option<std::cmp::Ordering> {
-        (self.v.retrieve()).partial_cmp(&BigInt::from(*other).v.retrieve())
+        (self.v.retrieve()).partial_cmp(&Self::from(*other).v.retrieve())
     }
 }
 
-impl PartialOrd<u32> for BigInt {
+impl<const LIMBS: usize> PartialOrd<u32> for BigInt<LIMBS> {
     fn partial_cmp(&self, other: &u32) -> Option<std::cmp::Ordering> {
-        (self.v.retrieve()).partial_cmp(&BigInt::from(*other).v.retrieve())
+        (self.v.retrieve()).partial_cmp(&Self::from(*other).v.retrieve())
     }
 }
 
-impl PartialOrd<u64> for BigInt {
+impl<const LIMBS: usize> PartialOrd<u64> for BigInt<LIMBS> {
     fn partial_cmp(&self, other: &u64) -> Option<std::cmp::Ordering> {
-        (self.v.retrieve()).partial_cmp(&BigInt::from(*other).v.retrieve())
+        Some(self.cmp_u128(*other as u128))
     }
 }
 
-impl PartialOrd<u128> for BigInt {
+impl<const LIMBS: usize> PartialOrd<u128> for BigInt<LIMBS> {
     fn partial_cmp(&self, other: &u128) -> Option<std::cmp::Ordering> {
-        (self.v.retrieve()).partial_cmp(&BigInt::from(*other).v.retrieve())
+        Some(self.cmp_u128(*other))
     }
 }
 
-impl BitAnd for BigInt {
-    type Output = BigInt;
+impl<const LIMBS: usize> BitAnd for BigInt<LIMBS> {
+    type Output = Self;
 
     fn bitand(self, rhs: Self) -> Self::Output {
-        BigInt {
+        Self {
             v: DynResidue::new(&(self.v.retrieve() & rhs.v.retrieve()), self.params()),
         }
     }
 }
 
-impl BitAnd<u16> for BigInt {
-    type Output = BigInt;
+impl<const LIMBS: usize> BitAnd<u16> for BigInt<LIMBS> {
+    type Output = Self;
 
     fn bitand(self, rhs: u16) -> Self::Output {
-        BigInt {
+        Self {
             v: DynResidue::new(
-                &(self.v.retrieve() & BigInt::from(rhs).v.retrieve()),
+                &(self.v.retrieve() & Self::from(rhs).v.retrieve()),
                 self.params(),
             ),
         }
     }
 }
 
-impl BitAnd<i32> for BigInt {
-    type Output = BigInt;
+impl<const LIMBS: usize> BitAnd<i32> for BigInt<LIMBS> {
+    type Output = Self;
 
     fn bitand(self, rhs: i32) -> Self::Output {
-        BigInt {
+        Self {
             v: DynResidue::new(
-                &(self.v.retrieve() & BigInt::from(rhs).v.retrieve()),
+                &(self.v.retrieve() & Self::from(rhs).v.retrieve()),
                 self.params(),
             ),
         }
     }
 }
 
-impl BitAnd<u32> for BigInt {
-    type Output = BigInt;
+impl<const LIMBS: usize> BitAnd<u32> for BigInt<LIMBS> {
+    type Output = Self;
 
     fn bitand(self, rhs: u32) -> Self::Output {
-        BigInt {
+        Self {
             v: DynResidue::new(
-                &(self.v.retrieve() & BigInt::from(rhs).v.retrieve()),
+                &(self.v.retrieve() & Self::from(rhs).v.retrieve()),
                 self.params(),
             ),
         }
     }
 }
 
-impl BitAnd<u64> for BigInt {
-    type Output = BigInt;
+impl<const LIMBS: usize> BitAnd<u64> for BigInt<LIMBS> {
+    type Output = Self;
 
     fn bitand(self, rhs: u64) -> Self::Output {
-        BigInt {
+        Self {
             v: DynResidue::new(
-                &(self.v.retrieve() & BigInt::from(rhs).v.retrieve()),
+                &(self.v.retrieve() & Self::new(BigIntType::U64(rhs)).v.retrieve()),
                 self.params(),
             ),
         }
     }
 }
 
-impl BitAnd<u128> for BigInt {
-    type Output = BigInt;
+impl<const LIMBS: usize> BitAnd<u128> for BigInt<LIMBS> {
+    type Output = Self;
 
     fn bitand(self, rhs: u128) -> Self::Output {
-        BigInt {
+        Self {
             v: DynResidue::new(
-                &(self.v.retrieve() & BigInt::from(rhs).v.retrieve()),
+                &(self.v.retrieve() & Self::new(BigIntType::U128(rhs)).v.retrieve()),
                 self.params(),
             ),
         }
     }
 }
 
-impl BitOr for BigInt {
-    type Output = BigInt;
+impl<const LIMBS: usize> BitOr for BigInt<LIMBS> {
+    type Output = Self;
 
     fn bitor(self, rhs: Self) -> Self::Output {
-        BigInt {
+        Self {
             v: DynResidue::new(&(self.v.retrieve() | rhs.v.retrieve()), self.params()),
         }
     }
 }
 
-impl BitOr<u16> for BigInt {
-    type Output = BigInt;
+impl<const LIMBS: usize> BitOr<u16> for BigInt<LIMBS> {
+    type Output = Self;
 
     fn bitor(self, rhs: u16) -> Self::Output {
-        BigInt {
+        Self {
             v: DynResidue::new(
-                &(self.v.retrieve() | BigInt::from(rhs).v.retrieve()),
+                &(self.v.retrieve() | Self::from(rhs).v.retrieve()),
                 self.params(),
             ),
         }
     }
 }
 
-impl BitOr<i32> for BigInt {
-    type Output = BigInt;
+impl<const LIMBS: usize> BitOr<i32> for BigInt<LIMBS> {
+    type Output = Self;
 
     fn bitor(self, rhs: i32) -> Self::Output {
-        BigInt {
+        Self {
             v: DynResidue::new(
-                &(self.v.retrieve() | BigInt::from(rhs).v.retrieve()),
+                &(self.v.retrieve() | Self::from(rhs).v.retrieve()),
                 self.params(),
             ),
         }
     }
 }
 
-impl BitOr<u32> for BigInt {
-    type Output = BigInt;
+impl<const LIMBS: usize> BitOr<u32> for BigInt<LIMBS> {
+    type Output = Self;
 
     fn bitor(self, rhs: u32) -> Self::Output {
-        BigInt {
+        Self {
             v: DynResidue::new(
-                &(self.v.retrieve() | BigInt::from(rhs).v.retrieve()),
+                &(self.v.retrieve() | Self::from(rhs).v.retrieve()),
                 self.params(),
             ),
         }
     }
 }
 
-impl BitOr<u64> for BigInt {
-    type Output = BigInt;
+impl<const LIMBS: usize> BitOr<u64> for BigInt<LIMBS> {
+    type Output = Self;
 
     fn bitor(self, rhs: u64) -> Self::Output {
-        BigInt {
+        Self {
             v: DynResidue::new(
-                &(self.v.retrieve() | BigInt::from(rhs).v.retrieve()),
+                &(self.v.retrieve() | Self::new(BigIntType::U64(rhs)).v.retrieve()),
                 self.params(),
             ),
         }
     }
 }
 
-impl BitOr<u128> for BigInt {
-    type Output = BigInt;
+impl<const LIMBS: usize> BitOr<u128> for BigInt<LIMBS> {
+    type Output = Self;
 
     fn bitor(self, rhs: u128) -> Self::Output {
-        BigInt {
+        Self {
             v: DynResidue::new(
-                &(self.v.retrieve() | BigInt::from(rhs).v.retrieve()),
+                &(self.v.retrieve() | Self::new(BigIntType::U128(rhs)).v.retrieve()),
                 self.params(),
             ),
         }
     }
 }
 
-impl Shl<usize> for BigInt {
-    type Output = BigInt;
+impl<const LIMBS: usize> Shl<usize> for BigInt<LIMBS> {
+    type Output = Self;
 
     fn shl(self, rhs: usize) -> Self::Output {
-        BigInt {
+        Self {
             v: DynResidue::new(&self.v.retrieve().shl_vartime(rhs), self.params()),
         }
     }
 }
 
-impl Shr<usize> for BigInt {
-    type Output = BigInt;
+impl<const LIMBS: usize> Shr<usize> for BigInt<LIMBS> {
+    type Output = Self;
 
     fn shr(self, rhs: usize) -> Self::Output {
-        BigInt {
+        Self {
             v: DynResidue::new(&self.v.retrieve().shr_vartime(rhs), self.params()),
         }
     }
 }
 
-impl ShrAssign<usize> for BigInt {
+impl<const LIMBS: usize> ShrAssign<usize> for BigInt<LIMBS> {
     fn shr_assign(&mut self, rhs: usize) {
         *self = *self >> rhs;
     }
 }
 
-impl ShlAssign<usize> for BigInt {
+impl<const LIMBS: usize> ShlAssign<usize> for BigInt<LIMBS> {
     fn shl_assign(&mut self, rhs: usize) {
         *self = *self << rhs;
     }
 }
 
-impl Display for BigInt {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        // concatenate bytes to string representation
-        let str: String = self
-            .v
-            .retrieve()
-            .to_words()
-            .iter()
-            .rev()
-            .map(|&x| x.to_string())
-            .join("");
+// largest power of `radix` that fits in a `u64`, along with its number of digits
+fn radix_chunk(radix: u32) -> (u64, usize) {
+    let mut chunk = radix as u64;
+    let mut width = 1;
+    while let Some(next) = chunk.checked_mul(radix as u64) {
+        chunk = next;
+        width += 1;
+    }
+    (chunk, width)
+}
+
+fn uint_to_str_radix<const LIMBS: usize>(x: Uint<LIMBS>, radix: u32) -> String {
+    assert!((2..=36).contains(&radix), "radix must be in 2..=36");
+    let (chunk, width) = radix_chunk(radix);
+    let chunk = NonZero::new(Limb::from(chunk)).unwrap();
+
+    let mut x = x;
+    let mut chunks: Vec<u64> = Vec::new();
+    loop {
+        let (q, r) = x.div_rem_limb(chunk);
+        chunks.push(r.0);
+        x = q;
+        if x == Uint::ZERO {
+            break;
+        }
+    }
+
+    let digits = |mut x: u64| {
+        let mut s: Vec<char> = Vec::new();
+        while x > 0 {
+            s.push(std::char::from_digit((x % radix as u64) as u32, radix).unwrap());
+            x /= radix as u64;
+        }
+        s.into_iter().rev().collect::<String>()
+    };
+    let mut s = digits(chunks.pop().unwrap());
+    if s.is_empty() {
+        s.push('0');
+    }
+    chunks
+        .iter()
+        .rev()
+        .for_each(|&x| s.push_str(&format!("{:0>width$}", digits(x), width = width)));
+    s
+}
+
+fn parse_uint<const LIMBS: usize>(s: &str, radix: u32) -> Result<Uint<LIMBS>, NttError> {
+    if !(2..=36).contains(&radix) {
+        return Err(NttError::InvalidRadix(radix));
+    }
+    let s = s.strip_prefix('+').unwrap_or(s);
+    if s.is_empty() {
+        return Err(NttError::EmptyString);
+    }
+    let (_, width) = radix_chunk(radix);
+
+    let mut value = Uint::<LIMBS>::ZERO;
+    for part in s.chars().collect_vec().chunks(width) {
+        let mut scale: u64 = 1;
+        let mut x: u64 = 0;
+        for &c in part {
+            let d = c.to_digit(radix).ok_or(NttError::InvalidDigit(c))?;
+            x = x * radix as u64 + d as u64;
+            scale *= radix as u64;
+        }
+        let shifted: Option<Uint<LIMBS>> = value.checked_mul(&Uint::<LIMBS>::from(scale)).into();
+        let sum: Option<Uint<LIMBS>> =
+            shifted.and_then(|v| Option::from(v.checked_add(&Uint::from(x))));
+        value = sum.ok_or(NttError::Overflow)?;
+    }
+    Ok(value)
+}
+
+impl<const LIMBS: usize> BigInt<LIMBS> {
+    // `value` under the odd `modulus`, rejecting values that are not already reduced
+    fn from_uint_parts(value: Uint<LIMBS>, modulus: Uint<LIMBS>) -> Result<Self, NttError> {
+        if !bool::from(modulus.bit(0)) {
+            return Err(NttError::EvenModulus);
+        }
+        if value >= modulus {
+            return Err(NttError::Overflow);
+        }
+        Ok(Self {
+            v: DynResidue::new(&value, DynResidueParams::new(&modulus)),
+        })
+    }
+
+    pub fn to_str_radix(&self, radix: u32) -> String {
+        uint_to_str_radix(self.v.retrieve(), radix)
+    }
+
+    pub fn from_str_radix(s: &str, radix: u32) -> Result<Self, NttError> {
+        Self::from_uint_parts(parse_uint(s, radix)?, Self::DEFAULT_MODULUS)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "BigInt")]
+struct BigIntText {
+    value: String,
+    modulus: String,
+}
 
-        let mut trimmed = str.trim_start_matches('0').to_string();
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "BigInt")]
+struct BigIntBytes {
+    value: Vec<u8>,
+    modulus: Vec<u8>,
+}
 
-        // in case the string is "0000"
-        if str.chars().all(|x| x == '0') {
-            trimmed = "0".to_string();
+// human-readable formats get decimal strings, binary formats get big-endian bytes
+#[cfg(feature = "serde")]
+impl<const LIMBS: usize> Serialize for BigInt<LIMBS> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let modulus = *self.params().modulus();
+        if serializer.is_human_readable() {
+            BigIntText {
+                value: self.to_str_radix(10),
+                modulus: uint_to_str_radix(modulus, 10),
+            }
+            .serialize(serializer)
+        } else {
+            let be_bytes = |x: Uint<LIMBS>| {
+                x.to_words()
+                    .iter()
+                    .rev()
+                    .flat_map(|w| w.to_be_bytes())
+                    .collect_vec()
+            };
+            BigIntBytes {
+                value: be_bytes(self.v.retrieve()),
+                modulus: be_bytes(modulus),
+            }
+            .serialize(serializer)
         }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const LIMBS: usize> Deserialize<'de> for BigInt<LIMBS> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (value, modulus) = if deserializer.is_human_readable() {
+            let text = BigIntText::deserialize(deserializer)?;
+            (
+                parse_uint(&text.value, 10).map_err(de::Error::custom)?,
+                parse_uint(&text.modulus, 10).map_err(de::Error::custom)?,
+            )
+        } else {
+            let bytes = BigIntBytes::deserialize(deserializer)?;
+            if bytes.value.len() != 8 * LIMBS || bytes.modulus.len() != 8 * LIMBS {
+                return Err(de::Error::custom(NttError::InvalidEncoding));
+            }
+            (
+                Uint::from_be_slice(&bytes.value),
+                Uint::from_be_slice(&bytes.modulus),
+            )
+        };
+        Self::from_uint_parts(value, modulus).map_err(de::Error::custom)
+    }
+}
+
+impl<const LIMBS: usize> FromStr for BigInt<LIMBS> {
+    type Err = NttError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_str_radix(s, 10)
+    }
+}
+
+impl<const LIMBS: usize> Display for BigInt<LIMBS> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad_integral(true, "", &self.to_str_radix(10))
+    }
+}
+
+impl<const LIMBS: usize> LowerHex for BigInt<LIMBS> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad_integral(true, "0x", &self.to_str_radix(16))
+    }
+}
 
-        write!(f, "{}", trimmed)
+impl<const LIMBS: usize> UpperHex for BigInt<LIMBS> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad_integral(true, "0x", &self.to_str_radix(16).to_uppercase())
     }
 }
 
-impl PolynomialFieldElement for BigInt {}
+impl<const LIMBS: usize> PolynomialFieldElement for BigInt<LIMBS> where
+    Self: TryFrom<u64> + TryFrom<u128> + TryFrom<usize>
+{
+}
+
+// Montgomery's trick: one inversion of the running product, then each inverse is recovered from
+// the prefix products. Elements go on the left of every product so that their modulus is kept.
+fn invert_chunk<T: PolynomialFieldElement>(v: &mut [T]) {
+    let one = T::from(1);
+    // prefix[i] is the product of the non-zero elements before `v[i]`
+    let mut prefix = Vec::with_capacity(v.len());
+    let mut acc = one;
+    v.iter().for_each(|&x| {
+        prefix.push(acc);
+        if !x.is_zero() {
+            acc = x * acc;
+        }
+    });
+
+    let mut inv = acc.invert();
+    v.iter_mut().zip(prefix).rev().for_each(|(x, p)| {
+        if !x.is_zero() {
+            let y = inv * p;
+            inv *= *x;
+            *x = y;
+        }
+    });
+}
+
+/// Replaces every element of `v` by its inverse under its own modulus, using a single inversion.
+/// Zeros are left in place; every other element must be a unit.
+#[cfg(feature = "parallel")]
+pub fn batch_invert<T: PolynomialFieldElement>(v: &mut [T]) {
+    let chunk = v.len().div_ceil(rayon::current_num_threads()).max(1);
+    v.par_chunks_mut(chunk).for_each(invert_chunk);
+}
+
+/// Replaces every element of `v` by its inverse under its own modulus, using a single inversion.
+/// Zeros are left in place; every other element must be a unit.
+#[cfg(not(feature = "parallel"))]
+pub fn batch_invert<T: PolynomialFieldElement>(v: &mut [T]) {
+    invert_chunk(v);
+}
 
 #[cfg(test)]
 mod tests {
-    use crate::numbers::BigInt;
+    use crate::{
+        error::NttError,
+        numbers::{
+            batch_invert, BigInt, BigInt1024, BigInt128, BigInt2048, BigInt256, BigInt512,
+            BigInt64, BigIntType, NttFieldElement,
+        },
+    };
+    use crypto_bigint::{modular::runtime_mod::DynResidueParams, Invert, Uint};
     use mod_exp::mod_exp;
 
     #[test]
@@ -805,48 +1241,286 @@ mod tests {
         (2..10).for_each(|x| {
             (2..10).for_each(|y| {
                 assert_eq!(
-                    BigInt::from(mod_exp(x, y, N)),
-                    BigInt::from(x).mod_exp(BigInt::from(y), BigInt::from(N))
+                    BigInt256::from(mod_exp(x, y, N)),
+                    BigInt256::from(x).mod_exp(BigInt256::from(y), BigInt256::from(N))
                 );
             })
         });
     }
 
+    #[test]
+    fn test_mod_exp_edges() {
+        // zero and one exponents, an even modulus, and exponents using the top bits
+        for (x, y, N) in [
+            (5_u128, 0_u128, 97_u128),
+            (5, 1, 97),
+            (3, 77, 1000),
+            (123456789, u64::MAX as u128, 998244353),
+            (2, u128::MAX, 0xffffffff00000001),
+        ] {
+            assert_eq!(
+                BigInt256::from(mod_exp(x, y, N)),
+                BigInt256::from(x).mod_exp(BigInt256::from(y), BigInt256::from(N))
+            );
+        }
+        let p = BigInt64::from(65537);
+        assert_eq!(BigInt64::from(3).mod_exp(p - 1, p), BigInt64::from(1));
+    }
+
     #[test]
     fn test_mul() {
-        let a = BigInt::from(8);
-        let b = BigInt::from(10);
+        let a = BigInt256::from(8);
+        let b = BigInt256::from(10);
         println!("{}", a * b);
     }
 
     #[test]
     fn test_is_even() {
-        let a = BigInt::from(1 << 12);
+        let a = BigInt256::from(1 << 12);
         assert!(a.is_even());
     }
 
     #[test]
     fn test_division() {
-        let a = BigInt::from(8);
-        let b = BigInt::from(10);
-        assert_eq!(a / b, BigInt::from(0))
+        let a = BigInt256::from(8);
+        let b = BigInt256::from(10);
+        assert_eq!(a / b, BigInt256::from(0))
     }
 
     #[test]
     fn test_rem() {
-        let a = BigInt::from(10);
-        assert_eq!(a.rem(BigInt::from(4)), BigInt::from(2));
+        let a = BigInt256::from(10);
+        assert_eq!(a.rem(BigInt256::from(4)), BigInt256::from(2));
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut a = BigInt256::from(10);
+        assert_eq!(a.set_mod(BigInt256::from(4)), Err(NttError::EvenModulus));
+        assert_eq!(a.to_u32(), Ok(10));
+        assert_eq!(
+            BigInt256::from(1_u64 << 40).to_u32(),
+            Err(NttError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_batch_invert() {
+        let p = BigInt256::from(998244353);
+        let mut v: Vec<BigInt256> = (0..1000_u32)
+            .map(|x| {
+                let mut x = BigInt256::from(x * x + 7 * x);
+                x.set_mod(p).unwrap();
+                x
+            })
+            .collect();
+        let expected: Vec<BigInt256> = v
+            .iter()
+            .map(|x| if x.is_zero() { *x } else { x.invert() })
+            .collect();
+        batch_invert(&mut v);
+        assert_eq!(v, expected);
+        assert!(v[0].is_zero());
+        assert_eq!(v[1] * BigInt256::from(8), BigInt256::from(1));
+
+        let mut empty: Vec<BigInt256> = vec![];
+        batch_invert(&mut empty);
     }
 
     #[test]
     fn test_display() {
-        let a = BigInt::from(111);
+        let a = BigInt256::from(111);
         println!("{}", a);
     }
 
+    #[test]
+    fn test_radix() {
+        let a = BigInt256::from(1_u128 << 64);
+        assert_eq!(a.to_string(), "18446744073709551616");
+        assert_eq!(format!("{:x}", a), "10000000000000000");
+        assert_eq!(format!("{:#X}", BigInt256::from(0xabcdef_u32)), "0xABCDEF");
+        assert_eq!(format!("{:>5}", BigInt256::from(0)), "    0");
+
+        let s = "115792089210356248762697446949407573529996955224135760342422259061068512044368";
+        let b: BigInt256 = s.parse().unwrap();
+        assert_eq!(b.to_string(), s);
+        assert_eq!(BigInt256::from_str_radix(&format!("{:x}", b), 16), Ok(b));
+        assert_eq!(
+            BigInt256::from_str_radix("deadbeefdeadbeefdeadbeefdeadbeef", 16),
+            Ok(BigInt256::from(0xdeadbeefdeadbeefdeadbeefdeadbeef_u128))
+        );
+        assert_eq!(BigInt256::from_str_radix("+101", 2), Ok(BigInt256::from(5)));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!("".parse::<BigInt256>(), Err(NttError::EmptyString));
+        assert_eq!("12a".parse::<BigInt256>(), Err(NttError::InvalidDigit('a')));
+        assert_eq!(
+            BigInt256::from_str_radix("1", 37),
+            Err(NttError::InvalidRadix(37))
+        );
+        // the default modulus itself is out of range
+        let n = "115792089210356248762697446949407573529996955224135760342422259061068512044369";
+        assert_eq!(n.parse::<BigInt256>(), Err(NttError::Overflow));
+        assert_eq!(
+            BigInt256::from_str_radix(&"f".repeat(65), 16),
+            Err(NttError::Overflow)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let mut a = BigInt256::from(1234567_u32);
+        a.set_mod(BigInt256::from(998244353)).unwrap();
+
+        let json = serde_json::to_string(&a).unwrap();
+        assert_eq!(json, r#"{"value":"1234567","modulus":"998244353"}"#);
+        let b: BigInt256 = serde_json::from_str(&json).unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a.params(), b.params());
+
+        let bytes = bincode::serialize(&a).unwrap();
+        assert_eq!(bytes.len(), 80);
+        let b: BigInt256 = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a.params(), b.params());
+
+        assert!(serde_json::from_str::<BigInt256>(r#"{"value":"7","modulus":"8"}"#).is_err());
+        assert!(serde_json::from_str::<BigInt256>(r#"{"value":"9","modulus":"7"}"#).is_err());
+    }
+
+    #[test]
+    fn test_widths() {
+        // the default 64-bit modulus is `2^64 - 1`, and nothing wraps around it
+        assert_eq!(
+            BigInt64::try_new(BigIntType::U128((1_u128 << 64) + 5)).unwrap_err(),
+            NttError::Overflow
+        );
+        assert_eq!(
+            BigInt64::try_new(BigIntType::U64(u64::MAX)).unwrap_err(),
+            NttError::Overflow
+        );
+        assert_eq!(
+            BigInt128::try_new(BigIntType::U128(u128::MAX)).unwrap_err(),
+            NttError::Overflow
+        );
+        assert_eq!(
+            BigInt64::try_new(BigIntType::U128(5)),
+            Ok(BigInt64::from(5))
+        );
+        assert_eq!(
+            BigInt64::from_str_radix(&"f".repeat(16), 16),
+            Err(NttError::Overflow)
+        );
+
+        // `From` exists only where the default modulus holds every value
+        assert_eq!(BigInt64::try_from(u64::MAX), Err(NttError::Overflow));
+        assert_eq!(BigInt64::try_from(u64::MAX - 1), Ok(-BigInt64::from(1)));
+        assert_eq!(BigInt64::try_from(i128::MIN), Err(NttError::Overflow));
+        assert_eq!(
+            BigInt64::from(i64::MIN),
+            -BigInt64::try_from(1_u64 << 63).unwrap()
+        );
+        assert_eq!(BigInt128::try_from(u128::MAX), Err(NttError::Overflow));
+        assert_eq!(
+            BigInt128::from(u64::MAX) + 1_u32,
+            BigInt128::try_from(1_u128 << 64).unwrap()
+        );
+        // comparisons need no conversion at all
+        assert!(BigInt64::from(0) < u64::MAX);
+        assert!(BigInt64::from(0) != u128::MAX);
+        assert!(BigInt256::from(u128::MAX) == u128::MAX);
+        assert!((BigInt256::from(1) << 128) > u128::MAX);
+        // nor does reducing a signed value below a narrow modulus
+        let q = BigInt64::from(998244353);
+        assert_eq!(BigInt64::from_signed(-(1 << 100), q), 115744635);
+        assert_eq!(BigInt64::from_signed(i128::MAX, q), 149780031);
+
+        let a = BigInt1024::from(1) << 500;
+        assert_eq!(a * a, BigInt1024::from(1) << 1000);
+        assert_eq!(format!("{:x}", a * a), format!("1{}", "0".repeat(250)));
+
+        let s = "340282366920938463463374607431768211457";
+        assert_eq!(s.parse::<BigInt2048>().unwrap().to_string(), s);
+
+        // the default parameters match those built the slow way
+        assert_eq!(
+            BigInt64::from(0).params(),
+            DynResidueParams::new(&Uint::<1>::MAX)
+        );
+        assert_eq!(
+            BigInt2048::from(0).params(),
+            DynResidueParams::new(&Uint::<32>::MAX)
+        );
+
+        // a value across all 8 limbs survives decimal, hex and word round trips
+        let x = (0..500)
+            .step_by(37)
+            .fold(BigInt512::from(0), |acc, k| acc + (BigInt512::from(1) << k))
+            + BigInt512::from(u64::MAX - 1);
+        assert_eq!(x.to_string().parse::<BigInt512>(), Ok(x));
+        assert_eq!(BigInt512::from_str_radix(&x.to_str_radix(16), 16), Ok(x));
+        let words = x.to_words();
+        assert_eq!(words.len(), 8);
+        let y = words.iter().rev().fold(BigInt512::from(0), |acc, &w| {
+            (acc << 64) + BigInt512::from(w)
+        });
+        assert_eq!(y, x);
+
+        // the deprecated conversion keeps the low 256 bits
+        #[allow(deprecated)]
+        let low = BigInt64::from(12345_u32).to_bigint();
+        assert_eq!(low, BigInt::from(12345_u32));
+    }
+
+    #[test]
+    fn test_signed() {
+        let q = BigInt256::from(3329);
+        let ONE = BigInt256::from(1);
+        assert_eq!(BigInt256::from_signed(-1, q), q - ONE);
+        assert_eq!(BigInt256::from_signed(-3330, q), q - ONE);
+        assert_eq!(BigInt256::from_signed(-3329, q), BigInt256::from(0));
+        assert_eq!(BigInt256::from_signed(3330, q), ONE);
+        let mut x = BigInt256::from_signed(-1, q);
+        x.set_mod(q).unwrap();
+        assert_eq!(x, q - ONE);
+        assert_eq!(x + ONE, BigInt256::from(0));
+        // the signed `From` impls work modulo the default modulus
+        assert_eq!(BigInt256::from(-1_i32), -ONE);
+        assert_eq!(BigInt256::from(-1_i64) + ONE, BigInt256::from(0));
+        assert_eq!(
+            BigInt256::from(-5_i128),
+            BigInt256::from(0) - BigInt256::from(5)
+        );
+        assert_eq!(BigInt256::from(i64::MAX), BigInt256::from(i64::MAX as u64));
+        assert_eq!(BigInt256::from(i128::MIN), -BigInt256::from(1_u128 << 127));
+
+        for (x, centered) in [(0, 0), (1, 1), (1664, 1664), (1665, -1664), (3328, -1)] {
+            assert_eq!(BigInt256::from(x).to_centered_i64(q), Ok(centered));
+        }
+        assert_eq!((q - BigInt256::from(7)).to_signed(q), Ok(-7));
+        // `q / 2` itself stays positive for even `q`
+        assert_eq!(BigInt256::from(2).to_signed(BigInt256::from(4)), Ok(2));
+
+        let q = BigInt256::from(1_u128 << 100);
+        assert_eq!((q - BigInt256::from(3)).to_signed(q), Ok(-3));
+        assert_eq!(
+            BigInt256::from(1_u128 << 70).to_centered_i64(q),
+            Err(NttError::Overflow)
+        );
+        assert_eq!(BigInt256::from(1_u128 << 70).to_signed(q), Ok(1 << 70));
+        let q = BigInt256::from(1_u128 << 127) << 2;
+        assert_eq!(
+            (BigInt256::from(1_u128 << 127) << 1).to_signed(q),
+            Err(NttError::Overflow)
+        );
+    }
+
     #[test]
     fn test_shr() {
-        let a = BigInt::from(1);
+        let a = BigInt256::from(1);
         println!("{}", a >> 1);
     }
 }
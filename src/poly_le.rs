@@ -0,0 +1,161 @@
+use std::cell::RefCell;
+
+use rand::RngCore;
+
+use crate::{
+    ntt::Constants,
+    polynomial::{Polynomial, PolynomialFieldElement},
+};
+
+// The arithmetic over `Z_p` that the polynomial algorithms share. Polynomials are bare
+// coefficient vectors, laid out as in `Polynomial`: lowest degree first, without trailing zeros,
+// so the zero polynomial is empty.
+//
+// Coefficients are held under the modulus `p` itself, so products reduce as they are formed and
+// each `rem(p)` is only a comparison. Values are moved back to the default modulus on the way out.
+
+pub(crate) fn bits<T: PolynomialFieldElement>(x: T) -> usize {
+    let words = x.to_words();
+    match words.iter().rposition(|&w| w != 0) {
+        Some(i) => 64 * i + 64 - words[i].leading_zeros() as usize,
+        None => 0,
+    }
+}
+
+// a length, an index or an exponent derived from one, which is far below the default modulus of
+// every width
+pub(crate) fn small<T: PolynomialFieldElement>(n: usize) -> T {
+    T::try_from(n).ok().expect("lengths fit at every width")
+}
+
+pub(crate) fn random_bits<T: PolynomialFieldElement, R: RngCore>(bits: usize, rng: &mut R) -> T {
+    let mut x = T::from(0);
+    let mut remaining = bits;
+    while remaining > 0 {
+        let take = remaining.min(32);
+        let chunk = (rng.next_u64() & ((1 << take) - 1)) as u32;
+        x = x * T::from(2).pow(take as u128) + T::from(chunk);
+        remaining -= take;
+    }
+    x
+}
+
+pub(crate) fn zero_mod<T: PolynomialFieldElement>(p: T) -> T {
+    let mut zero = T::from(0);
+    // an even `p` keeps the default modulus, which is only slower
+    let _ = zero.set_mod(p);
+    zero
+}
+
+pub(crate) fn sub_mod<T: PolynomialFieldElement>(x: T, y: T, p: T) -> T {
+    ((x + p) - y).rem(p)
+}
+
+// `p` must be prime
+pub(crate) fn inv_mod<T: PolynomialFieldElement>(x: T, p: T) -> T {
+    x.mod_exp(p - T::from(2), p)
+}
+
+pub(crate) fn trim<T: PolynomialFieldElement>(mut a: Vec<T>) -> Vec<T> {
+    while a.last().is_some_and(|x| x.is_zero()) {
+        a.pop();
+    }
+    a
+}
+
+pub(crate) fn to_le<T: PolynomialFieldElement>(a: Polynomial<T>, p: T) -> Vec<T> {
+    let zero = zero_mod(p);
+    trim(a.coef.iter().map(|&x| zero + x.rem(p)).collect())
+}
+
+// `x` moved back to the default modulus. Sums keep the modulus of their left operand, so adding
+// to a fresh zero is enough.
+pub(crate) fn to_default<T: PolynomialFieldElement>(x: T) -> T {
+    T::from(0) + x
+}
+
+pub(crate) fn from_le<T: PolynomialFieldElement>(a: Vec<T>) -> Polynomial<T> {
+    Polynomial::new(a.into_iter().map(to_default).collect())
+}
+
+pub(crate) fn add_poly<T: PolynomialFieldElement>(a: &[T], b: &[T], p: T) -> Vec<T> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut out = long.to_vec();
+    out.iter_mut()
+        .zip(short)
+        .for_each(|(x, &y)| *x = (*x + y).rem(p));
+    trim(out)
+}
+
+pub(crate) fn sub_poly<T: PolynomialFieldElement>(a: &[T], b: &[T], p: T) -> Vec<T> {
+    let zero = zero_mod(p);
+    let neg = b.iter().map(|&y| sub_mod(zero, y, p)).collect::<Vec<_>>();
+    add_poly(a, &neg, p)
+}
+
+// `b` must be non-zero
+pub(crate) fn div_rem_poly<T: PolynomialFieldElement>(a: &[T], b: &[T], p: T) -> (Vec<T>, Vec<T>) {
+    if a.len() < b.len() {
+        return (vec![], a.to_vec());
+    }
+    let m = b.len();
+    let lead = inv_mod(b[m - 1], p);
+    let mut r = a.to_vec();
+    let mut q = vec![zero_mod(p); a.len() - m + 1];
+    for i in (0..q.len()).rev() {
+        let k = (r[i + m - 1] * lead).rem(p);
+        q[i] = k;
+        if !k.is_zero() {
+            (0..m).for_each(|j| r[i + j] = sub_mod(r[i + j], (k * b[j]).rem(p), p));
+        }
+    }
+    r.truncate(m - 1);
+    (q, trim(r))
+}
+
+thread_local! {
+    // the words of the last `(N, w)` seen by `root_order`, with its result
+    static ROOT_ORDER: RefCell<Option<(Vec<u64>, usize)>> = const { RefCell::new(None) };
+}
+
+// order of `w`, which is a power of two for every `Constants` this crate builds. The recursive
+// algorithms ask again for the same constants on every product, so the last answer is kept.
+fn root_order<T: PolynomialFieldElement>(c: &Constants<T>) -> usize {
+    let key = [c.N.to_words(), c.w.to_words()].concat();
+    let cached = ROOT_ORDER.with(|r| match &*r.borrow() {
+        Some((k, order)) if *k == key => Some(*order),
+        _ => None,
+    });
+    if let Some(order) = cached {
+        return order;
+    }
+    let order = find_root_order(c);
+    ROOT_ORDER.with(|r| *r.borrow_mut() = Some((key, order)));
+    order
+}
+
+fn find_root_order<T: PolynomialFieldElement>(c: &Constants<T>) -> usize {
+    let one = T::from(1);
+    let mut x = c.w.rem(c.N);
+    let mut order = 1_usize;
+    while x != one {
+        if order == 1 << 63 {
+            return 0;
+        }
+        x = (x * x).rem(c.N);
+        order <<= 1;
+    }
+    order
+}
+
+// `c` with a root of order exactly `n`, as the transforms of length `n` need, if it has one
+pub(crate) fn transform_constants<T: PolynomialFieldElement>(
+    c: &Constants<T>,
+    n: usize,
+) -> Option<Constants<T>> {
+    let order = root_order(c);
+    (order >= n).then(|| Constants {
+        N: c.N,
+        w: c.w.mod_exp(small(order / n), c.N),
+    })
+}
@@ -5,13 +5,27 @@ use rayon::prelude::*;
 use std;
 use std::{
     fmt::Display,
+    hash::{Hash, Hasher},
     ops::{Add, AddAssign, Div, Index, Mul, MulAssign, Neg, ShrAssign, Sub},
 };
 
 use crypto_bigint::Invert;
 use itertools::{EitherOrBoth::*, Itertools};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::NttError,
+    ntt::*,
+    numbers::NttFieldElement,
+    poly_le::{small, transform_constants},
+};
 
-use crate::{ntt::*, numbers::NttFieldElement};
+pub use crate::{
+    evaluation::compose,
+    factorization::{factor, is_irreducible, roots},
+    ntt::{middle_product, try_middle_product},
+};
 
 pub trait PolynomialFieldElement:
     NttFieldElement
@@ -19,9 +33,9 @@ pub trait PolynomialFieldElement:
     + From<u16>
     + From<u32>
     + From<i32>
-    + From<u64>
-    + From<u128>
-    + From<usize>
+    + TryFrom<u64>
+    + TryFrom<u128>
+    + TryFrom<usize>
     + Clone
     + Copy
     + Add<Output = Self>
@@ -38,35 +52,79 @@ pub trait PolynomialFieldElement:
     + Send
     + Sync
 {
+    /// `x` as an element of `Z_q`, so a negative `-x` maps to `q - x`.
+    fn from_signed(x: i128, q: Self) -> Self {
+        let abs = x.unsigned_abs();
+        let r = match Self::try_from(abs) {
+            Ok(a) => a.rem(q),
+            // too wide for the default modulus, so fold it in 32 bits at a time below `q`
+            Err(_) => {
+                let shift = Self::from(1_u32 << 16) * Self::from(1_u32 << 16);
+                (0..4).rev().fold(Self::from(0_u32), |acc, k| {
+                    (acc * shift + Self::from((abs >> (32 * k)) as u32)).rem(q)
+                })
+            }
+        };
+        if x < 0 && !r.is_zero() {
+            q - r
+        } else {
+            r
+        }
+    }
 }
 
 pub trait PolynomialTrait<T: PolynomialFieldElement> {
     fn len(&self) -> usize;
     fn max(&self) -> T;
-    fn degree(&self) -> usize;
+    /// `None` for the zero polynomial.
+    fn degree(&self) -> Option<usize>;
+    fn try_degree(&self) -> Result<usize, NttError> {
+        self.degree().ok_or(NttError::ZeroPolynomial)
+    }
     fn to_vec(&self) -> Vec<T>;
     fn set_coef(&mut self, a: T, idx: usize);
     fn set_vec(&mut self, v: Vec<T>);
 }
 
+/// A polynomial with coefficients stored lowest degree first, so `coef[i]` belongs to `x^i`.
+/// `new` trims zero coefficients above the degree, so the zero polynomial has none at all.
+/// `set_coef` may leave some behind, which equality, hashing and `degree` ignore.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "Coefficients<T>"))]
 pub struct Polynomial<T: PolynomialFieldElement> {
     pub coef: Vec<T>,
 }
 
+// the serialized form of `Polynomial`, which goes through `Polynomial::new` to be trimmed
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+#[serde(rename = "Polynomial")]
+struct Coefficients<T: PolynomialFieldElement> {
+    coef: Vec<T>,
+}
+
+#[cfg(feature = "serde")]
+impl<T: PolynomialFieldElement> From<Coefficients<T>> for Polynomial<T> {
+    fn from(c: Coefficients<T>) -> Self {
+        Polynomial::new(c.coef)
+    }
+}
+
 impl<T: PolynomialFieldElement> PolynomialTrait<T> for Polynomial<T> {
     fn len(&self) -> usize {
         self.coef.len()
     }
 
-    fn degree(&self) -> usize {
-        let ZERO = T::from(0);
-        let start = self.coef.iter().position(|&x| x != ZERO).unwrap();
-        self.len() - start - 1
+    fn degree(&self) -> Option<usize> {
+        self.coef.iter().rposition(|x| !x.is_zero())
     }
 
     fn max(&self) -> T {
-        let mut ans = self.coef[0];
+        let Some(&first) = self.coef.first() else {
+            return T::from(0);
+        };
+        let mut ans = first;
 
         self.coef[1..].iter().for_each(|&x| {
             if ans < x {
@@ -81,18 +139,51 @@ impl<T: PolynomialFieldElement> PolynomialTrait<T> for Polynomial<T> {
         self.clone().coef
     }
 
+    // leaves the length alone, so a run of `set_coef` calls can zero and refill the top
     fn set_coef(&mut self, a: T, idx: usize) {
         self.coef[idx] = a;
     }
 
     fn set_vec(&mut self, v: Vec<T>) {
         self.coef = v;
+        self.normalize();
     }
 }
 
 impl<T: PolynomialFieldElement> Polynomial<T> {
+    /// Builds a polynomial from its coefficients lowest degree first, the storage order.
     pub fn new(coef: Vec<T>) -> Self {
-        Polynomial { coef }
+        let mut poly = Polynomial { coef };
+        poly.normalize();
+        poly
+    }
+
+    /// The same as `new`.
+    pub fn from_coeffs_le(coef: Vec<T>) -> Self {
+        Self::new(coef)
+    }
+
+    /// Builds a polynomial from its coefficients highest degree first.
+    pub fn from_coeffs_be(mut coef: Vec<T>) -> Self {
+        coef.reverse();
+        Self::new(coef)
+    }
+
+    /// Builds a polynomial over `Z_q` from signed coefficients, given in the same order as
+    /// `new`. A negative `-x` maps to `q - x`.
+    pub fn from_signed(coef: &[i64], q: T) -> Self {
+        Polynomial::new(coef.iter().map(|&x| T::from_signed(x as i128, q)).collect())
+    }
+
+    fn normalize(&mut self) {
+        while self.coef.last().is_some_and(|x| x.is_zero()) {
+            self.coef.pop();
+        }
+    }
+
+    // the coefficients up to the degree, should `coef` have been set directly
+    fn trimmed(&self) -> &[T] {
+        &self.coef[..self.degree().map_or(0, |d| d + 1)]
     }
 }
 
@@ -103,8 +194,11 @@ pub fn mul_brute<T: PolynomialFieldElement>(
     let a = lhs.len();
     let b = rhs.len();
     let ZERO = T::from(0_u32);
+    if a == 0 || b == 0 {
+        return Polynomial::new(vec![]);
+    }
 
-    let mut out: Vec<T> = vec![ZERO; a + b];
+    let mut out: Vec<T> = vec![ZERO; a + b - 1];
 
     for i in 0..a {
         for j in 0..b {
@@ -113,7 +207,7 @@ pub fn mul_brute<T: PolynomialFieldElement>(
         }
     }
 
-    Polynomial { coef: out }
+    Polynomial::new(out)
 }
 
 #[cfg(feature = "parallel")]
@@ -122,34 +216,36 @@ pub fn fast_mul<T: PolynomialFieldElement>(
     rhs: impl PolynomialTrait<T>,
     c: &Constants<T>,
 ) -> Polynomial<T> {
-    let v1_deg = lhs.degree();
-    let v2_deg = rhs.degree();
+    try_fast_mul(lhs, rhs, c).unwrap()
+}
+
+#[cfg(feature = "parallel")]
+pub fn try_fast_mul<T: PolynomialFieldElement>(
+    lhs: impl PolynomialTrait<T>,
+    rhs: impl PolynomialTrait<T>,
+    c: &Constants<T>,
+) -> Result<Polynomial<T>, NttError> {
+    let v1_deg = lhs.try_degree()?;
+    let v2_deg = rhs.try_degree()?;
     let n = (lhs.len() + rhs.len()).next_power_of_two();
     let ZERO = T::from(0);
 
-    let v1: Vec<T> = vec![ZERO; n - lhs.len()]
-        .into_iter()
-        .chain(lhs.to_vec().into_iter())
-        .collect();
-    let v2: Vec<T> = vec![ZERO; n - rhs.len()]
-        .into_iter()
-        .chain(rhs.to_vec().into_iter())
-        .collect();
+    let mut v1 = lhs.to_vec();
+    v1.resize(n, ZERO);
+    let mut v2 = rhs.to_vec();
+    v2.resize(n, ZERO);
 
-    let a_forward = forward(v1, &c);
-    let b_forward = forward(v2, &c);
+    let a_forward = try_forward(v1, &c)?;
+    let b_forward = try_forward(v2, &c)?;
 
     let mut mul = vec![ZERO; n as usize];
     mul.par_iter_mut()
         .enumerate()
         .for_each(|(i, x)| *x = (a_forward[i] * b_forward[i]).rem(c.N));
 
-    let coef = inverse(mul, &c);
-    // n - polynomial degree - 1
-    let start = n - (v1_deg + v2_deg + 1) - 1;
-    Polynomial {
-        coef: coef[start..=(start + v1_deg + v2_deg)].to_vec(),
-    }
+    let mut coef = try_inverse(mul, &c)?;
+    coef.truncate(v1_deg + v2_deg + 1);
+    Ok(Polynomial::new(coef))
 }
 
 #[cfg(not(feature = "parallel"))]
@@ -158,49 +254,162 @@ pub fn fast_mul<T: PolynomialFieldElement, P: PolynomialTrait<T>>(
     rhs: P,
     c: &Constants<T>,
 ) -> Polynomial<T> {
-    let v1_deg = lhs.degree();
-    let v2_deg = rhs.degree();
+    try_fast_mul(lhs, rhs, c).unwrap()
+}
+
+#[cfg(not(feature = "parallel"))]
+pub fn try_fast_mul<T: PolynomialFieldElement, P: PolynomialTrait<T>>(
+    lhs: P,
+    rhs: P,
+    c: &Constants<T>,
+) -> Result<Polynomial<T>, NttError> {
+    let v1_deg = lhs.try_degree()?;
+    let v2_deg = rhs.try_degree()?;
     let n = (lhs.len() + rhs.len()).next_power_of_two();
     let ZERO = T::from(0_u32);
 
-    let v1: Vec<T> = vec![ZERO; n - lhs.len()]
-        .into_iter()
-        .chain(lhs.to_vec().into_iter())
-        .collect();
-    let v2: Vec<T> = vec![ZERO; n - rhs.len()]
-        .into_iter()
-        .chain(rhs.to_vec().into_iter())
-        .collect();
+    let mut v1 = lhs.to_vec();
+    v1.resize(n, ZERO);
+    let mut v2 = rhs.to_vec();
+    v2.resize(n, ZERO);
 
-    let a_forward = forward(v1, &c);
-    let b_forward = forward(v2, &c);
+    let a_forward = try_forward(v1, &c)?;
+    let b_forward = try_forward(v2, &c)?;
 
     let mut mul = vec![ZERO; n as usize];
     mul.iter_mut()
         .enumerate()
         .for_each(|(i, x)| *x = (a_forward[i] * b_forward[i]).rem(c.N));
 
-    let coef = inverse(mul, &c);
-    // n - polynomial degree - 1
-    let start = n - (v1_deg + v2_deg + 1) - 1;
-    let res = Polynomial {
-        coef: coef[start..=(start + v1_deg + v2_deg)].to_vec(),
-    };
-    res
+    let mut coef = try_inverse(mul, &c)?;
+    coef.truncate(v1_deg + v2_deg + 1);
+    let res = Polynomial::new(coef);
+    Ok(res)
 }
 
-pub fn diff<T: PolynomialFieldElement, P: PolynomialTrait<T>>(mut poly: P) -> P {
-    let N = poly.len();
-    let _poly = poly.to_vec();
-    for n in (1..N).rev() {
-        poly.set_coef(_poly[n - 1] * T::from(N - n), n);
+// The blocked convolutions transform the short operand once at a length of about `8m` for
+// `m` coefficients, which keeps the cost per output near its minimum, and then run the long one
+// through in blocks of `size - m + 1`.
+struct Blocks<T: PolynomialFieldElement> {
+    long: Vec<T>,
+    m: usize,
+    size: usize,
+    short: Vec<T>,
+    c: Constants<T>,
+}
+
+impl<T: PolynomialFieldElement> Blocks<T> {
+    fn try_new(
+        lhs: impl PolynomialTrait<T>,
+        rhs: impl PolynomialTrait<T>,
+        c: &Constants<T>,
+    ) -> Result<Self, NttError> {
+        let mut a = lhs.to_vec();
+        a.truncate(lhs.try_degree()? + 1);
+        let mut b = rhs.to_vec();
+        b.truncate(rhs.try_degree()? + 1);
+        let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+        let m = short.len();
+        let size = (8 * m)
+            .next_power_of_two()
+            .min((long.len() + m - 1).next_power_of_two());
+        let c = transform_constants(c, size).ok_or(NttError::NoRootOfUnity)?;
+        let short = Self::transform(&short, size, &c)?;
+        Ok(Blocks {
+            long,
+            m,
+            size,
+            short,
+            c,
+        })
     }
-    let ZERO = T::from(0);
-    poly.set_coef(ZERO, 0);
-    let mut _poly = poly.to_vec();
-    let start = _poly.iter().position(|&x| x != ZERO).unwrap();
-    _poly = _poly[start..].to_vec();
-    poly.set_vec(_poly);
+
+    fn transform(v: &[T], size: usize, c: &Constants<T>) -> Result<Vec<T>, NttError> {
+        let mut v = v.to_vec();
+        v.resize(size, T::from(0));
+        try_forward(v, c)
+    }
+
+    // the cyclic convolution of `block` with the short operand
+    fn convolve(&self, block: &[T]) -> Result<Vec<T>, NttError> {
+        let mut v = Self::transform(block, self.size, &self.c)?;
+        v.iter_mut()
+            .zip(&self.short)
+            .for_each(|(x, &y)| *x = (*x * y).rem(self.c.N));
+        try_inverse(v, &self.c)
+    }
+}
+
+/// The product of `lhs` and `rhs` by overlap-add: the long operand is cut into blocks, each is
+/// convolved with the short one, and the overlapping tails are added up. `c.w` needs an order
+/// of at least the block transform length, about eight times the short operand's length.
+pub fn overlap_add<T: PolynomialFieldElement>(
+    lhs: impl PolynomialTrait<T>,
+    rhs: impl PolynomialTrait<T>,
+    c: &Constants<T>,
+) -> Polynomial<T> {
+    try_overlap_add(lhs, rhs, c).unwrap()
+}
+
+pub fn try_overlap_add<T: PolynomialFieldElement>(
+    lhs: impl PolynomialTrait<T>,
+    rhs: impl PolynomialTrait<T>,
+    c: &Constants<T>,
+) -> Result<Polynomial<T>, NttError> {
+    let blocks = Blocks::try_new(lhs, rhs, c)?;
+    let step = blocks.size - blocks.m + 1;
+    let mut out = vec![T::from(0); blocks.long.len() + blocks.m - 1];
+    for (i, block) in blocks.long.chunks(step).enumerate() {
+        out[i * step..]
+            .iter_mut()
+            .zip(blocks.convolve(block)?)
+            .for_each(|(x, y)| *x = (*x + y).rem(blocks.c.N));
+    }
+    Ok(Polynomial::new(out))
+}
+
+/// The product of `lhs` and `rhs` by overlap-save: overlapping windows of the long operand are
+/// convolved cyclically with the short one, and only the outputs free of wrap-around are kept.
+/// `c.w` needs the same order as for `overlap_add`.
+pub fn overlap_save<T: PolynomialFieldElement>(
+    lhs: impl PolynomialTrait<T>,
+    rhs: impl PolynomialTrait<T>,
+    c: &Constants<T>,
+) -> Polynomial<T> {
+    try_overlap_save(lhs, rhs, c).unwrap()
+}
+
+pub fn try_overlap_save<T: PolynomialFieldElement>(
+    lhs: impl PolynomialTrait<T>,
+    rhs: impl PolynomialTrait<T>,
+    c: &Constants<T>,
+) -> Result<Polynomial<T>, NttError> {
+    let blocks = Blocks::try_new(lhs, rhs, c)?;
+    let (size, m) = (blocks.size, blocks.m);
+    let len = blocks.long.len() + m - 1;
+    // the window starting at `i` of the long operand shifted up by `m - 1` yields outputs
+    // `i, ..., i + size - m`
+    let mut shifted = vec![T::from(0); m - 1];
+    shifted.extend_from_slice(&blocks.long);
+    let mut out = Vec::with_capacity(len + size);
+    while out.len() < len {
+        let start = out.len();
+        let window = &shifted[start..(start + size).min(shifted.len())];
+        out.extend_from_slice(&blocks.convolve(window)?[m - 1..]);
+    }
+    out.truncate(len);
+    Ok(Polynomial::new(out))
+}
+
+pub fn diff<T: PolynomialFieldElement, P: PolynomialTrait<T>>(mut poly: P) -> P {
+    let coef = poly.to_vec();
+    poly.set_vec(
+        coef.iter()
+            .enumerate()
+            .skip(1)
+            .map(|(i, &x)| x * small(i))
+            .collect(),
+    );
     poly
 }
 
@@ -208,20 +417,17 @@ impl<T: PolynomialFieldElement> Add<Polynomial<T>> for Polynomial<T> {
     type Output = Polynomial<T>;
 
     fn add(self, rhs: Polynomial<T>) -> Self::Output {
-        Polynomial {
-            coef: self
-                .coef
+        Polynomial::new(
+            self.coef
                 .iter()
-                .rev()
-                .zip_longest(rhs.coef.iter().rev())
+                .zip_longest(rhs.coef.iter())
                 .map(|p| match p {
                     Both(&a, &b) => a + b,
                     Left(&a) => a,
                     Right(&b) => b,
                 })
-                .rev()
                 .collect(),
-        }
+        )
     }
 }
 
@@ -237,14 +443,32 @@ impl<T: PolynomialFieldElement> Neg for Polynomial<T> {
     type Output = Polynomial<T>;
 
     fn neg(self) -> Self::Output {
-        Polynomial {
-            coef: self.coef.iter().map(|a| -(*a)).collect(),
-        }
+        Polynomial::new(self.coef.iter().map(|a| -(*a)).collect())
+    }
+}
+
+// compares and hashes the coefficients up to the degree, so a `coef` with zeros left above it
+// still matches its normalized form
+impl<T: PolynomialFieldElement> PartialEq for Polynomial<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.trimmed() == other.trimmed()
     }
 }
 
+impl<T: PolynomialFieldElement + Eq> Eq for Polynomial<T> {}
+
+impl<T: PolynomialFieldElement + Hash> Hash for Polynomial<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.trimmed().hash(state);
+    }
+}
+
+// in storage order, lowest degree first
 impl<T: PolynomialFieldElement> Display for Polynomial<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.coef.is_empty() {
+            return write!(f, "0 ");
+        }
         self.coef.iter().map(|&x| write!(f, "{} ", x)).collect()
     }
 }
@@ -259,38 +483,52 @@ impl<T: PolynomialFieldElement> Index<usize> for Polynomial<T> {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
+
     use itertools::Itertools;
     use rand::Rng;
 
     use super::Polynomial;
     use crate::{
+        error::NttError,
         ntt::{working_modulus, Constants},
-        numbers::BigInt,
-        polynomial::{diff, fast_mul, PolynomialTrait},
+        numbers::{BigInt128, BigInt2048, BigInt256, BigInt512, BigInt64},
+        poly_le::small,
+        polynomial::{
+            diff, fast_mul, middle_product, mul_brute, overlap_add, overlap_save, try_fast_mul,
+            try_middle_product, try_overlap_add, try_overlap_save, PolynomialFieldElement,
+            PolynomialTrait,
+        },
+        test_utils::{big, constants, random_poly, random_vec, reduced, P},
     };
 
     #[test]
     fn test_add() {
-        let a = Polynomial::new(vec![1, 2, 3, 4].iter().map(|&x| BigInt::from(x)).collect());
-        let b = Polynomial::new(vec![1, 2].iter().map(|&x| BigInt::from(x)).collect());
+        let a = Polynomial::new(
+            vec![1, 2, 3, 4]
+                .iter()
+                .map(|&x| BigInt256::from(x))
+                .collect(),
+        );
+        let b = Polynomial::new(vec![1, 2].iter().map(|&x| BigInt256::from(x)).collect());
         println!("{}", a + b);
     }
 
     #[test]
     fn test_mul() {
-        let ONE = BigInt::from(1);
+        let ONE = BigInt256::from(1);
         (0..10).for_each(|_| {
             let n: usize = 1 << rand::thread_rng().gen::<usize>() % (1 << 3);
-            let v1: Vec<BigInt> = (0..n)
-                .map(|_| BigInt::from(rand::thread_rng().gen::<u32>() % (1 << 6)))
+            let v1: Vec<BigInt256> = (0..n)
+                .map(|_| BigInt256::from(rand::thread_rng().gen::<u32>() % (1 << 6)))
                 .collect();
-            let v2: Vec<BigInt> = (0..n)
-                .map(|_| BigInt::from(rand::thread_rng().gen::<u32>() % (1 << 6)))
+            let v2: Vec<BigInt256> = (0..n)
+                .map(|_| BigInt256::from(rand::thread_rng().gen::<u32>() % (1 << 6)))
                 .collect();
             let a = Polynomial::new(vec![ONE].into_iter().chain(v1.into_iter()).collect_vec());
             let b = Polynomial::new(vec![ONE].into_iter().chain(v2.into_iter()).collect_vec());
 
-            let N = BigInt::from((a.len() + b.len()).next_power_of_two());
+            let N = BigInt256::from((a.len() + b.len()).next_power_of_two());
             let M = (*a
                 .coef
                 .iter()
@@ -307,17 +545,179 @@ mod tests {
         });
     }
 
+    fn check_fast_mul<T: PolynomialFieldElement>(M: T) {
+        let a = Polynomial::new((1..=7_u32).map(T::from).collect_vec());
+        let b = Polynomial::new((1..=5_u32).map(|x| T::from(3 * x)).collect_vec());
+        let N = small::<T>((a.len() + b.len()).next_power_of_two());
+        let c = working_modulus(N, M);
+        let expected = mul_brute(a.clone(), b.clone()).coef;
+        assert!(fast_mul(a, b, &c).coef == expected[..11]);
+    }
+
+    #[test]
+    fn test_middle_product() {
+        let c = constants();
+        let random = |n: usize| random_vec(n, P, &mut rand::thread_rng());
+        // the `n` by `2n - 1` shape of a Newton step, a power-of-two `m`, and `n = 1` and `n = m`
+        for (n, m) in [(100, 199), (64, 256), (1, 30), (30, 30), (7, 20)] {
+            let a = random(n);
+            let b = random(m);
+            let mut full = vec![BigInt256::from(0); n + m - 1];
+            (0..n).for_each(|i| {
+                (0..m).for_each(|j| full[i + j] = (full[i + j] + a[i] * b[j]).rem(c.N))
+            });
+            assert_eq!(middle_product(&a, &b, &c), full[n - 1..m]);
+        }
+        assert_eq!(
+            try_middle_product(&random(5), &random(4), &c).unwrap_err(),
+            NttError::LengthMismatch(5, 4)
+        );
+        assert_eq!(
+            try_middle_product(&[], &random(4), &c).unwrap_err(),
+            NttError::LengthMismatch(0, 4)
+        );
+    }
+
+    #[test]
+    fn test_overlap() {
+        let c = constants();
+        let random = |n: usize| random_poly(n, P, &mut rand::thread_rng());
+        // a short filter over a long signal, either way round, and operands of similar length
+        for (n, m) in [(3000, 64), (40, 1000), (500, 1), (300, 200)] {
+            let a = random(n);
+            let b = random(m);
+            let expected = reduced(mul_brute(a.clone(), b.clone()), c.N);
+            assert_eq!(overlap_add(a.clone(), b.clone(), &c), expected);
+            assert_eq!(overlap_save(a, b, &c), expected);
+        }
+
+        // a root of order 2^9 is too small for the blocks of a filter of 100
+        let small = working_modulus(BigInt256::from(1 << 9), BigInt256::from(952));
+        assert_eq!(
+            try_overlap_add(random(1000), random(100), &small).unwrap_err(),
+            NttError::NoRootOfUnity
+        );
+        assert_eq!(
+            try_overlap_save(random(10), Polynomial::new(vec![]), &c).unwrap_err(),
+            NttError::ZeroPolynomial
+        );
+    }
+
+    #[test]
+    fn test_widths() {
+        check_fast_mul(BigInt64::from(1_u32 << 20));
+        check_fast_mul(BigInt128::from(1_u64 << 50));
+        check_fast_mul(BigInt256::from(1_u128 << 100));
+        check_fast_mul(BigInt512::from(1) << 200);
+        check_fast_mul(BigInt2048::from(1_u32 << 20));
+    }
+
+    #[test]
+    fn test_from_signed() {
+        let q = BigInt256::from(3329);
+        let a = Polynomial::from_signed(&[-1, 0, 5, -3329, -3330, 1664], q);
+        let expected = [3328, 0, 5, 0, 3328, 1664].map(BigInt256::from);
+        assert_eq!(a.coef, expected.to_vec());
+        let centered = a.coef.iter().map(|x| x.to_centered_i64(q).unwrap());
+        assert_eq!(centered.collect_vec(), vec![-1, 0, 5, 0, -1, 1664]);
+    }
+
     #[test]
     fn test_diff() {
-        let a = Polynomial::new(vec![3, 2, 1].iter().map(|&x| BigInt::from(x)).collect());
+        let a = Polynomial::new(vec![3, 2, 1].iter().map(|&x| BigInt256::from(x)).collect());
         let da = diff(a);
         println!("{}", da);
+        assert_eq!(da.coef, vec![BigInt256::from(2), BigInt256::from(2)]);
+
+        let a = Polynomial::new(vec![BigInt256::from(5)]);
+        assert_eq!(diff(a).degree(), None);
+    }
+
+    #[test]
+    fn test_zero_polynomial() {
+        let ZERO = BigInt256::from(0);
+        let a = Polynomial::new(vec![ZERO, ZERO]);
+        let b = Polynomial::new(vec![BigInt256::from(1), BigInt256::from(2)]);
+        let c = working_modulus(BigInt256::from(4), BigInt256::from(5));
+        assert!(a.coef.is_empty());
+        assert_eq!(a.degree(), None);
+        assert_eq!(a.try_degree().unwrap_err(), NttError::ZeroPolynomial);
+        assert_eq!(
+            try_fast_mul(a, b, &c).unwrap_err(),
+            NttError::ZeroPolynomial
+        );
+    }
+
+    #[test]
+    fn test_normalized() {
+        // 3x^2 + 2x + 1, written both ways and with zeros above the degree
+        let a = Polynomial::from_coeffs_le(big(&[1, 2, 3, 0, 0]));
+        assert_eq!(a.coef, big(&[1, 2, 3]));
+        assert_eq!(a.degree(), Some(2));
+        assert_eq!(a[2], BigInt256::from(3));
+        assert_eq!(a, Polynomial::from_coeffs_be(big(&[0, 3, 2, 1])));
+        assert_eq!(a.to_string(), "1 2 3 ");
+
+        // cancelling the leading term lowers the degree
+        let b = Polynomial::new(big(&[5, 0, 3]));
+        assert_eq!((a.clone() - b).degree(), Some(1));
+        assert!((a.clone() - a.clone()).coef.is_empty());
+
+        // equal polynomials hash alike, however `coef` was set
+        let mut c = a.clone();
+        c.coef.push(BigInt256::from(0));
+        assert_eq!(a, c);
+        let set: HashSet<_> = [a.clone(), c, Polynomial::new(big(&[]))]
+            .into_iter()
+            .collect();
+        assert_eq!(set.len(), 2);
+
+        // `set_coef` keeps the length, so the top coefficient can be zeroed and set again
+        let mut d = a.clone();
+        d.set_coef(BigInt256::from(0), 2);
+        assert_eq!(d.degree(), Some(1));
+        assert_eq!(d, Polynomial::new(big(&[1, 2])));
+        d.set_coef(BigInt256::from(7), 2);
+        d.set_coef(BigInt256::from(0), 1);
+        assert_eq!(d, Polynomial::new(big(&[1, 0, 7])));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let c = working_modulus(BigInt256::from(8), BigInt256::from(65));
+        let a = Polynomial::new(vec![3, 2, 1].iter().map(|&x| BigInt256::from(x)).collect());
+
+        let json = serde_json::to_string(&a).unwrap();
+        let b: Polynomial<BigInt256> = serde_json::from_str(&json).unwrap();
+        assert_eq!(a.coef, b.coef);
+        let b: Polynomial<BigInt256> =
+            bincode::deserialize(&bincode::serialize(&a).unwrap()).unwrap();
+        assert_eq!(a.coef, b.coef);
+
+        // trailing zeros are trimmed on the way in, as by `Polynomial::new`
+        let mut padded = a.clone();
+        padded.coef.push(BigInt256::from(0));
+        let b: Polynomial<BigInt256> =
+            serde_json::from_str(&serde_json::to_string(&padded).unwrap()).unwrap();
+        assert_eq!(a.coef, b.coef);
+
+        let d: Constants<BigInt256> =
+            serde_json::from_str(&serde_json::to_string(&c).unwrap()).unwrap();
+        assert_eq!((c.N, c.w), (d.N, d.w));
+        let d: Constants<BigInt256> =
+            bincode::deserialize(&bincode::serialize(&c).unwrap()).unwrap();
+        assert_eq!((c.N, c.w), (d.N, d.w));
+        assert_eq!(
+            fast_mul(a.clone(), a.clone(), &c).coef,
+            fast_mul(a.clone(), a, &d).coef
+        );
     }
 
     #[test]
     fn test_comparator() {
-        let a = BigInt::from(550338105);
-        let b = BigInt::from(1);
+        let a = BigInt256::from(550338105);
+        let b = BigInt256::from(1);
         assert!(a > b);
         let p = Polynomial::new(vec![a, b]);
         let hi = p.max();
@@ -1,9 +1,29 @@
-use crate::{numbers::BigInt, polynomial::PolynomialFieldElement};
+use std::cmp::Ordering;
 
-fn miller_test<T: PolynomialFieldElement>(mut d: T, n: T, x: T) -> bool {
+use rand::RngCore;
+
+use crate::{
+    error::NttError,
+    ntt::Constants,
+    poly_le::{bits, random_bits, small},
+    polynomial::PolynomialFieldElement,
+};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+// Miller-Rabin with these bases is deterministic below 3.3 * 10^24
+const SMALL_PRIMES: [u32; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+const TRIAL_DIVISION_BOUND: u32 = 1 << 12;
+const RHO_BATCH: usize = 128;
+// `random_ntt_prime` scans every candidate when there are at most `2^SCAN_BITS` of them, and
+// otherwise gives up after `RANDOM_TRIES` random draws per bit, where a prime is expected within
+// about one draw per bit
+const SCAN_BITS: usize = 12;
+const RANDOM_TRIES: usize = 64;
+
+fn miller_test<T: PolynomialFieldElement>(mut d: T, n: T, a: T) -> bool {
     let ONE = T::from(1);
     let TWO = T::from(2);
-    let a = TWO + x;
 
     let mut x = a.mod_exp(d, n);
     match x.set_mod(n) {
@@ -38,11 +58,17 @@ fn miller_test<T: PolynomialFieldElement>(mut d: T, n: T, x: T) -> bool {
 
 pub fn is_prime<T: PolynomialFieldElement>(num: T) -> bool {
     let ONE = T::from(1);
-    if num <= ONE || num == T::from(4) {
+    if num <= ONE {
         return false;
     }
-    if num <= T::from(3) {
-        return true;
+    for &p in SMALL_PRIMES.iter() {
+        let p = T::from(p);
+        if num == p {
+            return true;
+        }
+        if num.rem(p).is_zero() {
+            return false;
+        }
     }
 
     let mut d = num - ONE;
@@ -50,20 +76,434 @@ pub fn is_prime<T: PolynomialFieldElement>(num: T) -> bool {
         d >>= 1;
     }
 
-    for x in 0..4 {
-        if miller_test(d, num, T::from(x)) == false {
-            return false;
+    SMALL_PRIMES
+        .iter()
+        .all(|&a| miller_test(d, num, T::from(a)))
+}
+
+pub fn gcd<T: PolynomialFieldElement>(a: T, b: T) -> T {
+    let (mut a, mut b) = (a, b);
+    while !b.is_zero() {
+        let r = a.rem(b);
+        a = b;
+        b = r;
+    }
+    a
+}
+
+// Brent's variant of Pollard's rho; `n` must be odd and composite
+fn pollard_rho<T: PolynomialFieldElement>(n: T) -> T {
+    let one = T::from(1);
+    let mut c = one;
+    loop {
+        let f = |x: T| x * x + c;
+        let mut y = T::from(2);
+        let mut q = one;
+        let _ = y.set_mod(n);
+        let _ = q.set_mod(n);
+        let (mut x, mut ys) = (y, y);
+        let mut g = one;
+        let mut r = 1;
+        while g == one {
+            x = y;
+            (0..r).for_each(|_| y = f(y));
+            let mut k = 0;
+            while k < r && g == one {
+                ys = y;
+                (0..RHO_BATCH.min(r - k)).for_each(|_| {
+                    y = f(y);
+                    q *= x - y;
+                });
+                g = gcd(n, q);
+                k += RHO_BATCH;
+            }
+            r *= 2;
+        }
+        if g == n {
+            // the batched product hit zero, so backtrack one step at a time
+            loop {
+                ys = f(ys);
+                g = gcd(n, x - ys);
+                if g != one {
+                    break;
+                }
+            }
+        }
+        if g != n {
+            return g;
+        }
+        c += one;
+    }
+}
+
+fn split<T: PolynomialFieldElement>(n: T, out: &mut Vec<T>) {
+    if is_prime(n) {
+        out.push(n);
+        return;
+    }
+    let d = pollard_rho(n);
+    split(d, out);
+    split(n / d, out);
+}
+
+// strips the prime factors below `TRIAL_DIVISION_BOUND`, returning them with the cofactor
+pub(crate) fn trial_divide<T: PolynomialFieldElement>(n: T) -> (Vec<T>, T) {
+    let mut n = n;
+    let mut primes: Vec<T> = Vec::new();
+
+    let mut d = 2;
+    while d < TRIAL_DIVISION_BOUND && T::from(d * d) <= n {
+        let p = T::from(d);
+        while n.rem(p).is_zero() {
+            primes.push(p);
+            n = n / p;
+        }
+        d += if d == 2 { 1 } else { 2 };
+    }
+    (primes, n)
+}
+
+pub(crate) fn group_factors<T: PolynomialFieldElement>(mut primes: Vec<T>) -> Vec<(T, usize)> {
+    primes.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    primes.into_iter().fold(Vec::new(), |mut acc, p| {
+        match acc.last_mut() {
+            Some((q, e)) if *q == p => *e += 1,
+            _ => acc.push((p, 1)),
+        }
+        acc
+    })
+}
+
+/// Factors `n` into primes, returning `(prime, exponent)` pairs in increasing order.
+pub fn factor<T: PolynomialFieldElement>(n: T) -> Vec<(T, usize)> {
+    let one = T::from(1);
+    let (mut primes, n) = trial_divide(n);
+    if n > one {
+        split(n, &mut primes);
+    }
+    group_factors(primes)
+}
+
+#[cfg(feature = "parallel")]
+pub(crate) fn is_primitive_root<T: PolynomialFieldElement>(
+    g: T,
+    p: T,
+    factors: &[(T, usize)],
+) -> bool {
+    let one = T::from(1);
+    let totient = p - one;
+    factors
+        .par_iter()
+        .all(|&(q, _)| g.mod_exp(totient / q, p) != one)
+}
+
+#[cfg(not(feature = "parallel"))]
+pub(crate) fn is_primitive_root<T: PolynomialFieldElement>(
+    g: T,
+    p: T,
+    factors: &[(T, usize)],
+) -> bool {
+    let ONE = T::from(1);
+    let totient = p - ONE;
+    factors
+        .iter()
+        .all(|&(q, _)| g.mod_exp(totient / q, p) != ONE)
+}
+
+/// Returns the smallest generator of the multiplicative group modulo the prime `p`.
+pub fn primitive_root<T: PolynomialFieldElement>(p: T) -> T {
+    let one = T::from(1);
+    if p == T::from(2) {
+        return one;
+    }
+    let factors = factor(p - one);
+    let mut g = T::from(2);
+    while !is_primitive_root(g, p, &factors) {
+        g += one;
+    }
+    g
+}
+
+/// Returns a primitive `n`-th root of unity modulo the prime `p`; `n` must divide `p - 1`.
+pub fn root_of_unity<T: PolynomialFieldElement>(p: T, n: T) -> T {
+    try_root_of_unity(p, n).unwrap()
+}
+
+pub fn try_root_of_unity<T: PolynomialFieldElement>(p: T, n: T) -> Result<T, NttError> {
+    let one = T::from(1);
+    if p <= one || n.is_zero() {
+        return Err(NttError::NoRootOfUnity);
+    }
+    let totient = p - one;
+    if !totient.rem(n).is_zero() {
+        return Err(NttError::NoRootOfUnity);
+    }
+    // only the prime factors of `n` matter, so `p - 1` itself is never factored
+    let factors = factor(n);
+    let mut g = T::from(2);
+    while g < p {
+        let w = g.mod_exp(totient / n, p);
+        if factors.iter().all(|&(q, _)| w.mod_exp(n / q, p) != one) {
+            return Ok(w);
+        }
+        g += one;
+    }
+    Err(NttError::NoRootOfUnity)
+}
+
+fn pow2<T: PolynomialFieldElement>(k: usize) -> T {
+    T::from(2).pow(k as u128)
+}
+
+// primitive `n`-th root of unity mod `p`, where `n` is a power of two dividing `p - 1`
+pub(crate) fn two_adic_root<T: PolynomialFieldElement>(p: T, n: T) -> T {
+    let one = T::from(1);
+    let totient = p - one;
+    let half = totient / T::from(2);
+    let mut z = T::from(2);
+    // a quadratic non-residue has the full 2-power part of `p - 1` in its order, though it need
+    // not generate the whole group
+    while z.mod_exp(half, p) != totient {
+        z += one;
+    }
+    z.mod_exp(totient / n, p)
+}
+
+/// Samples a random prime `p` of exactly `bits` bits with `p = 1 (mod 2^two_adicity)`.
+/// The returned `w` is a root of unity of order `2^two_adicity`, which generates only that
+/// subgroup; `primitive_root(p)` generates the whole multiplicative group.
+pub fn random_ntt_prime<T: PolynomialFieldElement, R: RngCore>(
+    bits: usize,
+    two_adicity: usize,
+    rng: &mut R,
+) -> Constants<T> {
+    try_random_ntt_prime(bits, two_adicity, rng).unwrap()
+}
+
+pub fn try_random_ntt_prime<T: PolynomialFieldElement, R: RngCore>(
+    bits: usize,
+    two_adicity: usize,
+    rng: &mut R,
+) -> Result<Constants<T>, NttError> {
+    if two_adicity + 2 > bits {
+        return Err(NttError::NoPrime);
+    }
+    let one = T::from(1);
+    let k = two_adicity.max(1);
+    let top = pow2::<T>(bits - 1);
+    let step = pow2::<T>(k);
+    let found = |q: T| {
+        let w = two_adic_root(q, pow2(two_adicity));
+        Constants { N: q, w }
+    };
+
+    // the candidates are `top + i step + 1` for `i < 2^(bits - 1 - k)`
+    if bits - 1 - k <= SCAN_BITS {
+        let count = 1_usize << (bits - 1 - k);
+        let start = rng.next_u64() as usize % count;
+        return (0..count)
+            .map(|i| top + small::<T>((start + i) % count) * step + one)
+            .find(|&q| is_prime(q))
+            .map(found)
+            .ok_or(NttError::NoPrime);
+    }
+    (0..RANDOM_TRIES * bits)
+        .map(|_| {
+            let x = top + random_bits::<T, R>(bits - 1, rng);
+            (x / step) * step + one
+        })
+        .find(|&q| is_prime(q))
+        .map(found)
+        .ok_or(NttError::NoPrime)
+}
+
+/// Returns the smallest prime `p` of exactly `bits` bits with `p = 1 (mod n)`, or `None` if
+/// there is none or `n` is not a power of two. The returned `w` is a root of unity of order `n`,
+/// not a generator of the multiplicative group.
+pub fn next_ntt_prime<T: PolynomialFieldElement>(bits: usize, n: usize) -> Option<Constants<T>> {
+    try_next_ntt_prime(bits, n).ok()
+}
+
+pub fn try_next_ntt_prime<T: PolynomialFieldElement>(
+    bits: usize,
+    n: usize,
+) -> Result<Constants<T>, NttError> {
+    if bits == 0 {
+        return Err(NttError::NoPrime);
+    }
+    try_next_ntt_prime_after(pow2(bits - 1), n)
+}
+
+/// Returns the smallest prime above `p` of the same bit length with `p = 1 (mod n)`, so that
+/// repeated calls walk through distinct primes of that size, or `None` if there are no more.
+/// `n` must be a power of two and the returned `w` is a root of unity of order `n`.
+pub fn next_ntt_prime_after<T: PolynomialFieldElement>(p: T, n: usize) -> Option<Constants<T>> {
+    try_next_ntt_prime_after(p, n).ok()
+}
+
+pub fn try_next_ntt_prime_after<T: PolynomialFieldElement>(
+    p: T,
+    n: usize,
+) -> Result<Constants<T>, NttError> {
+    if !n.is_power_of_two() {
+        return Err(NttError::NonPowerOfTwoLength(n));
+    }
+    let bits = bits(p);
+    if n.trailing_zeros() as usize + 1 >= bits {
+        return Err(NttError::NoPrime);
+    }
+    let one = T::from(1);
+    let step = small(n.max(2));
+    let end = pow2::<T>(bits);
+    // the first `q = 1 (mod step)` above `p`
+    let mut q = p - (p - one).rem(step) + step;
+    while q < end {
+        if is_prime(q) {
+            let w = two_adic_root(q, small(n));
+            return Ok(Constants { N: q, w });
         }
+        q += step;
     }
-    true
+    Err(NttError::NoPrime)
 }
+
 #[cfg(test)]
 mod tests {
-    use crate::{numbers::BigInt, prime::is_prime};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::{
+        error::NttError,
+        ntt::working_modulus,
+        numbers::BigInt256,
+        prime::{
+            factor, is_prime, next_ntt_prime, next_ntt_prime_after, primitive_root,
+            random_ntt_prime, root_of_unity, try_next_ntt_prime, try_next_ntt_prime_after,
+            try_random_ntt_prime,
+        },
+    };
 
     #[test]
     fn test_is_prime() {
-        assert!(is_prime(BigInt::from(11)));
-        assert!(!is_prime(BigInt::from(10)));
+        assert!(is_prime(BigInt256::from(11)));
+        assert!(!is_prime(BigInt256::from(10)));
+        assert!(is_prime(BigInt256::from(5)));
+        // strong pseudoprime to bases 2, 3, 5 and 7
+        assert!(!is_prime(BigInt256::from(3215031751_u64)));
+        assert!(is_prime(BigInt256::from((1_u128 << 127) - 1)));
+    }
+
+    #[test]
+    fn test_factor() {
+        let to_pairs = |v: &[(u128, usize)]| {
+            v.iter()
+                .map(|&(p, e)| (BigInt256::from(p), e))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(factor(BigInt256::from(1)), vec![]);
+        assert_eq!(
+            factor(BigInt256::from(600851475143_u64)),
+            to_pairs(&[(71, 1), (839, 1), (1471, 1), (6857, 1)])
+        );
+        assert_eq!(
+            factor(BigInt256::from(1_u128 << 64) + BigInt256::from(1)),
+            to_pairs(&[(274177, 1), (67280421310721, 1)])
+        );
+        let p = (1_u128 << 31) - 1;
+        let q = (1_u128 << 61) - 1;
+        assert_eq!(
+            factor(
+                BigInt256::from(p) * BigInt256::from(p) * BigInt256::from(q) * BigInt256::from(12)
+            ),
+            to_pairs(&[(2, 2), (3, 1), (p, 2), (q, 1)])
+        );
+    }
+
+    #[test]
+    fn test_primitive_root() {
+        assert_eq!(primitive_root(BigInt256::from(7)), BigInt256::from(3));
+        assert_eq!(
+            primitive_root(BigInt256::from(998244353)),
+            BigInt256::from(3)
+        );
+
+        let ONE = BigInt256::from(1);
+        let p = BigInt256::from(998244353);
+        let n = BigInt256::from(1 << 23);
+        let w = root_of_unity(p, n);
+        assert_eq!(w.mod_exp(n, p), ONE);
+        assert_ne!(w.mod_exp(BigInt256::from(1 << 22), p), ONE);
+
+        let c = working_modulus(BigInt256::from(1 << 10), BigInt256::from(1_u128 << 100));
+        assert_eq!(c.w.mod_exp(BigInt256::from(1 << 10), c.N), ONE);
+        assert_ne!(c.w.mod_exp(BigInt256::from(1 << 9), c.N), ONE);
+    }
+
+    #[test]
+    fn test_random_ntt_prime() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let ONE = BigInt256::from(1);
+        [(50, 16), (60, 20), (60, 1)].iter().for_each(|&(bits, k)| {
+            (0..4).for_each(|_| {
+                let c = random_ntt_prime::<BigInt256, _>(bits, k, &mut rng);
+                assert!(is_prime(c.N));
+                assert!(c.N >= BigInt256::from(1_u128 << (bits - 1)));
+                assert!(c.N < BigInt256::from(1_u128 << bits));
+                assert!((c.N - ONE).rem(BigInt256::from(1_u128 << k)).is_zero());
+                assert_eq!(c.w.mod_exp(BigInt256::from(1_u128 << k), c.N), ONE);
+                assert_ne!(c.w.mod_exp(BigInt256::from(1_u128 << (k - 1)), c.N), ONE);
+            });
+        });
+
+        // the only candidates, 33 and 49, are composite
+        assert_eq!(
+            try_random_ntt_prime::<BigInt256, _>(6, 4, &mut rng).unwrap_err(),
+            NttError::NoPrime
+        );
+        // 97 and 113 are found by the scan of a small range
+        let c = random_ntt_prime::<BigInt256, _>(7, 4, &mut rng);
+        assert!(c.N == BigInt256::from(97) || c.N == BigInt256::from(113));
+    }
+
+    #[test]
+    fn test_next_ntt_prime() {
+        let ONE = BigInt256::from(1);
+        let n = 1 << 10;
+        let c = next_ntt_prime::<BigInt256>(30, n).unwrap();
+        assert_eq!(c.N, BigInt256::from(536881153));
+        assert_eq!(c.w.mod_exp(BigInt256::from(n), c.N), ONE);
+        assert_ne!(c.w.mod_exp(BigInt256::from(n / 2), c.N), ONE);
+        assert!(next_ntt_prime::<BigInt256>(6, 16).is_none());
+        // bad arguments are `None` too, rather than a panic
+        assert!(next_ntt_prime::<BigInt256>(30, 24).is_none());
+        assert!(next_ntt_prime::<BigInt256>(4, 16).is_none());
+        assert_eq!(
+            try_next_ntt_prime::<BigInt256>(6, 16).unwrap_err(),
+            NttError::NoPrime
+        );
+        assert_eq!(
+            try_next_ntt_prime::<BigInt256>(30, 24).unwrap_err(),
+            NttError::NonPowerOfTwoLength(24)
+        );
+
+        // distinct 50-bit primes, one after another
+        let mut p = BigInt256::from(1_u64 << 49);
+        let primes = (0..4)
+            .map(|_| {
+                p = next_ntt_prime_after(p, 1 << 16).unwrap().N;
+                p
+            })
+            .collect::<Vec<_>>();
+        assert!(primes.windows(2).all(|w| w[0] < w[1]));
+        assert!(primes.iter().all(|&q| is_prime(q)
+            && q < BigInt256::from(1_u64 << 50)
+            && (q - ONE).rem(BigInt256::from(1 << 16)).is_zero()));
+        assert_eq!(
+            next_ntt_prime_after(c.N, n).unwrap().N,
+            BigInt256::from(536896513)
+        );
+        // 97 and 113 are the only 7-bit primes `1 (mod 16)`
+        let after = |p: u32| try_next_ntt_prime_after(BigInt256::from(p), 16).map(|c| c.N);
+        assert_eq!(after(97), Ok(BigInt256::from(113)));
+        assert_eq!(after(113), Err(NttError::NoPrime));
     }
 }
@@ -0,0 +1,218 @@
+use crate::{
+    error::NttError,
+    multiply::mul_poly,
+    ntt::Constants,
+    poly_le::{from_le, inv_mod, sub_mod, to_le, trim, zero_mod},
+    polynomial::{Polynomial, PolynomialFieldElement, PolynomialTrait},
+};
+
+/// The minimal polynomial `x^L + c_1 x^(L - 1) + ... + c_L` of the sequence `s` over `Z_p`, for a
+/// prime `p`, so that `s[n] + c_1 s[n - 1] + ... + c_L s[n - L] = 0` for every `n >= L`. It is
+/// unique once `s` has at least `2L` terms.
+pub fn berlekamp_massey<T: PolynomialFieldElement>(s: &[T], p: T) -> Polynomial<T> {
+    let zero = zero_mod(p);
+    let s = s.iter().map(|&x| zero + x.rem(p)).collect::<Vec<_>>();
+    // connection polynomials, little-endian, with `cur` the current one and `prev` the one
+    // before the last length change
+    let mut cur = vec![zero + T::from(1)];
+    let mut prev = cur.clone();
+    let mut len = 0;
+    let mut shift = 1;
+    let mut last = zero + T::from(1);
+    for n in 0..s.len() {
+        let d = (1..=len.min(cur.len() - 1)).fold(s[n], |acc, i| (acc + cur[i] * s[n - i]).rem(p));
+        if d.is_zero() {
+            shift += 1;
+            continue;
+        }
+        let k = (d * inv_mod(last, p)).rem(p);
+        let mut next = cur.clone();
+        next.resize(next.len().max(prev.len() + shift), zero);
+        prev.iter()
+            .enumerate()
+            .for_each(|(i, &b)| next[i + shift] = sub_mod(next[i + shift], (k * b).rem(p), p));
+        if 2 * len <= n {
+            len = n + 1 - len;
+            prev = cur;
+            last = d;
+            shift = 1;
+        } else {
+            shift += 1;
+        }
+        cur = next;
+    }
+    // `cur` is the reversal of the minimal polynomial of degree `len`
+    cur.resize(len + 1, zero);
+    Polynomial::from_coeffs_be(cur.iter().map(|&x| T::from(0) + x).collect())
+}
+
+// the coefficients of `a(x)` at even or odd powers, as a polynomial in `x^2`
+fn half<T: PolynomialFieldElement>(a: &[T], odd: bool) -> Vec<T> {
+    trim(a.iter().skip(odd as usize).step_by(2).copied().collect())
+}
+
+// `a(-x)`
+fn negate_odd<T: PolynomialFieldElement>(a: &[T], p: T) -> Vec<T> {
+    let zero = zero_mod(p);
+    a.iter()
+        .enumerate()
+        .map(|(i, &x)| if i % 2 == 1 { sub_mod(zero, x, p) } else { x })
+        .collect()
+}
+
+/// The coefficient of `x^k` in the power series `num / den` over `Z_p`, with `p = c.N`, by
+/// Bostan-Mori in `O(d log d log k)` for `d = deg den`. Errors with `NotInvertible` if
+/// `den(0) = 0`.
+pub fn nth_term<T: PolynomialFieldElement>(
+    num: Polynomial<T>,
+    den: Polynomial<T>,
+    mut k: u64,
+    c: &Constants<T>,
+) -> Result<T, NttError> {
+    let p = c.N;
+    let mut u = to_le(num, p);
+    let mut v = to_le(den, p);
+    if v.first().is_none_or(|x| x.is_zero()) {
+        return Err(NttError::NotInvertible);
+    }
+    // [x^k] u(x) / v(x) = [x^k] u(x) v(-x) / v(x) v(-x), whose denominator is even
+    while k > 0 && !u.is_empty() {
+        let w = negate_odd(&v, p);
+        u = half(&mul_poly(&u, &w, c), k % 2 == 1);
+        v = half(&mul_poly(&v, &w, c), false);
+        k /= 2;
+    }
+    // `u` only runs out when the remaining coefficients are all zero
+    let Some(&u0) = u.first() else {
+        return Ok(T::from(0));
+    };
+    Ok(T::from(0) + (u0 * inv_mod(v[0], p)).rem(p))
+}
+
+/// The `k`-th term of the sequence with minimal polynomial `m`, as from `berlekamp_massey`,
+/// starting with `init`, which needs at least `deg m` terms. This is Fiduccia's reduction to
+/// `nth_term`, with `p = c.N`.
+pub fn linear_recurrence<T: PolynomialFieldElement>(
+    init: &[T],
+    m: Polynomial<T>,
+    k: u64,
+    c: &Constants<T>,
+) -> Result<T, NttError> {
+    let p = c.N;
+    let d = m.try_degree()?;
+    if init.len() < d {
+        return Err(NttError::TooFewTerms(d));
+    }
+    // the reversal of `m` is the denominator, and the numerator is its product with the first
+    // `d` terms, truncated to degree `d - 1`
+    let zero = zero_mod(p);
+    let lift = |v: &[T]| trim(v.iter().map(|&x| zero + x.rem(p)).collect());
+    let den = m.coef[..=d].iter().rev().copied().collect::<Vec<_>>();
+    let mut num = mul_poly(&lift(&init[..d]), &lift(&den), c);
+    num.truncate(d);
+    nth_term(from_le(trim(num)), Polynomial::new(den), k, c)
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use crate::{
+        error::NttError,
+        numbers::BigInt256,
+        polynomial::Polynomial,
+        recurrence::{berlekamp_massey, linear_recurrence, nth_term},
+        test_utils::{big, constants, P},
+    };
+
+    // `len` terms of `s[n] = sum c[i] s[n - 1 - i]`, starting with `init`
+    fn sequence(init: &[u64], c: &[u64], len: usize) -> Vec<u64> {
+        let mut s = init.to_vec();
+        while s.len() < len {
+            let n = s.len();
+            let x = c
+                .iter()
+                .enumerate()
+                .fold(0, |acc, (i, &x)| (acc + x * s[n - 1 - i]) % P);
+            s.push(x);
+        }
+        s
+    }
+
+    #[test]
+    fn test_berlekamp_massey() {
+        let fib = sequence(&[0, 1], &[1, 1], 20);
+        let m = berlekamp_massey(&big(&fib), BigInt256::from(P));
+        assert_eq!(m.coef, big(&[P - 1, P - 1, 1]));
+
+        // a random recurrence of order 24 is recovered from 48 terms
+        let mut rng = StdRng::seed_from_u64(3);
+        let c = (0..24).map(|_| rng.gen_range(1..P)).collect_vec();
+        let init = (0..24).map(|_| rng.gen_range(0..P)).collect_vec();
+        let s = sequence(&init, &c, 48);
+        let m = berlekamp_massey(&big(&s), BigInt256::from(P));
+        let expected = c.iter().rev().map(|&x| P - x).chain([1]).collect_vec();
+        assert_eq!(m.coef, big(&expected));
+
+        // zeros have the empty recurrence, a geometric sequence an order-one one
+        assert_eq!(
+            berlekamp_massey(&big(&[0; 6]), BigInt256::from(P)).coef,
+            big(&[1])
+        );
+        let m = berlekamp_massey(&big(&[5, 15, 45, 135]), BigInt256::from(P));
+        assert_eq!(m.coef, big(&[P - 3, 1]));
+    }
+
+    #[test]
+    fn test_nth_term() {
+        let c = constants();
+        // 1 / (1 - x)^2 = sum (k + 1) x^k
+        let num = Polynomial::new(big(&[1]));
+        let den = Polynomial::new(big(&[1, P - 2, 1]));
+        for k in [0, 1, 7, 1 << 40] {
+            assert_eq!(
+                nth_term(num.clone(), den.clone(), k, &c).unwrap(),
+                BigInt256::from((k + 1) % P)
+            );
+        }
+        assert_eq!(
+            nth_term(num, Polynomial::new(big(&[0, 1])), 3, &c).unwrap_err(),
+            NttError::NotInvertible
+        );
+    }
+
+    #[test]
+    fn test_linear_recurrence() {
+        let c = constants();
+        let fib = sequence(&[0, 1], &[1, 1], 100);
+        let m = berlekamp_massey(&big(&fib), BigInt256::from(P));
+        assert_eq!(
+            linear_recurrence(&big(&fib), m.clone(), 90, &c).unwrap(),
+            BigInt256::from(fib[90])
+        );
+        // Fibonacci numbers have period 2(p + 1) modulo this `p`, as 5 is not a square mod `p`
+        let period = 2 * (P + 1);
+        assert_eq!(
+            linear_recurrence(&big(&fib), m.clone(), 17 * period + 50, &c).unwrap(),
+            BigInt256::from(fib[50])
+        );
+        assert_eq!(
+            linear_recurrence(&big(&fib[..1]), m, 5, &c).unwrap_err(),
+            NttError::TooFewTerms(2)
+        );
+
+        // an order large enough that the products go through the NTT
+        let mut rng = StdRng::seed_from_u64(5);
+        let coef = (0..100).map(|_| rng.gen_range(0..P)).collect_vec();
+        let init = (0..100).map(|_| rng.gen_range(0..P)).collect_vec();
+        let s = sequence(&init, &coef, 3000);
+        let m = berlekamp_massey(&big(&s[..200]), BigInt256::from(P));
+        for k in [0, 99, 100, 2999] {
+            assert_eq!(
+                linear_recurrence(&big(&s), m.clone(), k as u64, &c).unwrap(),
+                BigInt256::from(s[k])
+            );
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use crate::{poly_le::bits, polynomial::PolynomialFieldElement};
+
+pub trait QuadraticResidue: Sized {
+    /// Legendre symbol `(self / p)` for an odd prime `p`: `0`, `1` or `-1`.
+    fn legendre(&self, p: Self) -> i8;
+    /// Jacobi symbol `(self / n)` for an odd `n`.
+    fn jacobi(&self, n: Self) -> i8;
+    /// Whether `self` is a square mod the odd prime `p`. Unlike the Legendre convention, `0`
+    /// counts, as it has the square root `0`; `legendre(p) == 1` leaves it out.
+    fn is_quadratic_residue(&self, p: Self) -> bool;
+    /// The smaller square root of `self` mod the odd prime `p`, or `None` if there is none. The
+    /// root is returned under the modulus `p`.
+    fn sqrt(&self, p: Self) -> Option<Self>;
+}
+
+impl<T: PolynomialFieldElement> QuadraticResidue for T {
+    fn legendre(&self, p: T) -> i8 {
+        let one = T::from(1);
+        let a = self.rem(p);
+        if a.is_zero() {
+            return 0;
+        }
+        if a.mod_exp((p - one) / T::from(2), p) == one {
+            1
+        } else {
+            -1
+        }
+    }
+
+    fn jacobi(&self, n: T) -> i8 {
+        let one = T::from(1);
+        let (eight, four) = (T::from(8), T::from(4));
+        let (three, five) = (T::from(3), T::from(5));
+        let mut a = self.rem(n);
+        let mut n = n;
+        let mut t = 1;
+        while !a.is_zero() {
+            while a.is_even() {
+                a >>= 1;
+                let r = n.rem(eight);
+                if r == three || r == five {
+                    t = -t;
+                }
+            }
+            std::mem::swap(&mut a, &mut n);
+            if a.rem(four) == three && n.rem(four) == three {
+                t = -t;
+            }
+            a = a.rem(n);
+        }
+        if n == one {
+            t
+        } else {
+            0
+        }
+    }
+
+    fn is_quadratic_residue(&self, p: T) -> bool {
+        self.legendre(p) >= 0
+    }
+
+    fn sqrt(&self, p: T) -> Option<T> {
+        let one = T::from(1);
+        let a = self.rem(p);
+        if a.legendre(p) == -1 {
+            return None;
+        }
+        let mut r = if a.is_zero() {
+            a
+        } else if p.rem(T::from(4)) == T::from(3) {
+            a.mod_exp((p + one) / T::from(4), p)
+        } else {
+            let (_, s) = odd_part(p - one);
+            // Tonelli-Shanks takes up to `s^2` multiplications, Cipolla a fixed multiple of `log p`
+            if s * s > 8 * bits(p) {
+                cipolla(a, p)
+            } else {
+                tonelli_shanks(a, p)
+            }
+        };
+        r.set_mod(p).ok()?;
+        let neg = -r;
+        Some(if neg < r { neg } else { r })
+    }
+}
+
+// `n = q * 2^s` with `q` odd
+fn odd_part<T: PolynomialFieldElement>(n: T) -> (T, usize) {
+    let mut q = n;
+    let mut s = 0;
+    while q.is_even() {
+        q >>= 1;
+        s += 1;
+    }
+    (q, s)
+}
+
+fn non_residue<T: PolynomialFieldElement>(p: T) -> T {
+    let mut z = T::from(2);
+    while z.legendre(p) != -1 {
+        z += T::from(1);
+    }
+    z
+}
+
+// `a` must be a non-zero quadratic residue mod `p`
+pub(crate) fn tonelli_shanks<T: PolynomialFieldElement>(a: T, p: T) -> T {
+    let one = T::from(1);
+    let (q, mut m) = odd_part(p - one);
+    let mut c = non_residue(p).mod_exp(q, p);
+    let mut t = a.mod_exp(q, p);
+    let mut r = a.mod_exp((q + one) / T::from(2), p);
+    while t != one {
+        // least `i` with `t^(2^i) = 1`
+        let mut i = 0;
+        let mut x = t;
+        while x != one {
+            x = (x * x).rem(p);
+            i += 1;
+        }
+        let b = (0..m - i - 1).fold(c, |b, _| (b * b).rem(p));
+        m = i;
+        c = (b * b).rem(p);
+        t = (t * c).rem(p);
+        r = (r * b).rem(p);
+    }
+    r
+}
+
+// `a` must be a non-zero quadratic residue mod `p`. Computes `(u + sqrt(u^2 - a))^((p + 1) / 2)`
+// in `F_p[x] / (x^2 - (u^2 - a))`, where `u^2 - a` is a non-residue.
+pub(crate) fn cipolla<T: PolynomialFieldElement>(a: T, p: T) -> T {
+    let (zero, one) = (T::from(0), T::from(1));
+    let sub = |x: T, y: T| if x < y { (x + p) - y } else { x - y };
+
+    let mut u = one;
+    let mut w = sub((u * u).rem(p), a);
+    while w.legendre(p) != -1 {
+        u += one;
+        w = sub((u * u).rem(p), a);
+    }
+
+    let mul = |(x0, x1): (T, T), (y0, y1): (T, T)| {
+        (
+            ((x0 * y0).rem(p) + (((x1 * y1).rem(p)) * w).rem(p)).rem(p),
+            ((x0 * y1).rem(p) + (x1 * y0).rem(p)).rem(p),
+        )
+    };
+    let mut e = (p + one) / T::from(2);
+    let mut base = (u, one);
+    let mut res = (one, zero);
+    while !e.is_zero() {
+        if !e.is_even() {
+            res = mul(res, base);
+        }
+        base = mul(base, base);
+        e >>= 1;
+    }
+    res.0
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        numbers::{BigInt256, BigInt64},
+        residue::{cipolla, tonelli_shanks, QuadraticResidue},
+    };
+
+    #[test]
+    fn test_legendre() {
+        let p = BigInt256::from(769);
+        let mut is_square = vec![false; 769];
+        (1..769_usize).for_each(|x| is_square[x * x % 769] = true);
+        (0..769_usize).for_each(|x| {
+            let a = BigInt256::from(x);
+            let expected = match (x, is_square[x]) {
+                (0, _) => 0,
+                (_, true) => 1,
+                _ => -1,
+            };
+            assert_eq!(a.legendre(p), expected);
+            assert_eq!(a.jacobi(p), expected);
+        });
+    }
+
+    #[test]
+    fn test_jacobi() {
+        let n = BigInt256::from(15);
+        let expected = [0, 1, 1, 0, 1, 0, 0, -1, 1, 0, 0, -1, 0, -1, -1];
+        (0..15_u32).for_each(|a| assert_eq!(BigInt256::from(a).jacobi(n), expected[a as usize]));
+        assert_eq!(BigInt256::from(1001).jacobi(BigInt256::from(9907)), -1);
+    }
+
+    #[test]
+    fn test_sqrt() {
+        // 3 (mod 4), small and large 2-adicity
+        for p in [10007_u64, 769, 998244353, 0xffffffff00000001] {
+            let p = BigInt256::from(p);
+            (1..40_u32).map(BigInt256::from).for_each(|x| {
+                let a = (x * x).rem(p);
+                let r = a.sqrt(p).unwrap();
+                assert_eq!((r * r).rem(p), a);
+                assert!(r <= p - r);
+                assert!(a.is_quadratic_residue(p));
+            });
+        }
+
+        let p = BigInt256::from(998244353);
+        let a = BigInt256::from(1234567);
+        let sq = (a * a).rem(p);
+        for r in [tonelli_shanks(sq, p), cipolla(sq, p)] {
+            assert!(r == a || r == p - a);
+        }
+        assert_eq!(BigInt256::from(3).sqrt(p), None);
+        assert_eq!(BigInt256::from(0).sqrt(p), Some(BigInt256::from(0)));
+        assert!(BigInt256::from(0).is_quadratic_residue(p));
+        assert!(!BigInt256::from(3).is_quadratic_residue(p));
+
+        let p = BigInt64::from(65537);
+        let mut a = BigInt64::from(2);
+        a.set_mod(p).unwrap();
+        let r = a.sqrt(p).unwrap();
+        assert_eq!(r * r, a);
+    }
+}
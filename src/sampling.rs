@@ -0,0 +1,286 @@
+use rand::{seq::index, Rng, RngCore};
+use sha3::{
+    digest::{ExtendableOutput, Update, XofReader},
+    Shake128,
+};
+
+use crate::{
+    encoding::{chunk, from_words},
+    error::NttError,
+    ntt::Constants,
+    poly_le::{bits, random_bits},
+    polynomial::{Polynomial, PolynomialFieldElement},
+};
+
+// the Gaussian tail beyond this many standard deviations is cut off
+const TAIL_CUT: f64 = 12.0;
+
+/// `n` coefficients drawn uniformly from `[0, q)`, by rejection of `bits(q - 1)`-bit samples.
+pub fn uniform<T: PolynomialFieldElement, R: RngCore>(
+    n: usize,
+    q: T,
+    rng: &mut R,
+) -> Polynomial<T> {
+    let d = bits(q - T::from(1));
+    let sample = |rng: &mut R| loop {
+        let x: T = random_bits(d, rng);
+        if x < q {
+            return x;
+        }
+    };
+    Polynomial::new((0..n).map(|_| sample(rng)).collect())
+}
+
+/// Coefficients `sum(a_i - b_i)` over `eta` pairs of random bits, as in Kyber and Dilithium.
+pub fn centered_binomial<T: PolynomialFieldElement, R: RngCore>(
+    n: usize,
+    eta: u32,
+    q: T,
+    rng: &mut R,
+) -> Polynomial<T> {
+    let coef: Vec<i64> = (0..n)
+        .map(|_| {
+            (0..eta)
+                .map(|_| (rng.next_u32() & 1) as i64 - (rng.next_u32() & 1) as i64)
+                .sum()
+        })
+        .collect();
+    Polynomial::from_signed(&coef, q)
+}
+
+/// Coefficients from the discrete Gaussian of width `sigma` centered at zero, by rejection
+/// from the uniform distribution on `[-12 sigma, 12 sigma]`. Not constant-time. Panics unless
+/// `sigma` is positive and finite, see `try_discrete_gaussian`.
+pub fn discrete_gaussian<T: PolynomialFieldElement, R: RngCore>(
+    n: usize,
+    sigma: f64,
+    q: T,
+    rng: &mut R,
+) -> Polynomial<T> {
+    try_discrete_gaussian(n, sigma, q, rng).unwrap()
+}
+
+pub fn try_discrete_gaussian<T: PolynomialFieldElement, R: RngCore>(
+    n: usize,
+    sigma: f64,
+    q: T,
+    rng: &mut R,
+) -> Result<Polynomial<T>, NttError> {
+    if !(sigma > 0.0 && sigma.is_finite()) {
+        return Err(NttError::InvalidSigma);
+    }
+    // `x * x` must not overflow for any `x` within the bound
+    if TAIL_CUT * sigma >= (1_u64 << 31) as f64 {
+        return Err(NttError::Overflow);
+    }
+    let bound = (TAIL_CUT * sigma).ceil() as i64;
+    let sample = |rng: &mut R| loop {
+        let x = rng.gen_range(-bound..=bound);
+        let rho = (-((x * x) as f64) / (2.0 * sigma * sigma)).exp();
+        if rng.gen::<f64>() < rho {
+            return x;
+        }
+    };
+    let coef: Vec<i64> = (0..n).map(|_| sample(rng)).collect();
+    Ok(Polynomial::from_signed(&coef, q))
+}
+
+/// Coefficients drawn uniformly from `{-1, 0, 1}`.
+pub fn ternary<T: PolynomialFieldElement, R: RngCore>(
+    n: usize,
+    q: T,
+    rng: &mut R,
+) -> Polynomial<T> {
+    let coef: Vec<i64> = (0..n).map(|_| rng.gen_range(-1..=1)).collect();
+    Polynomial::from_signed(&coef, q)
+}
+
+/// Exactly `h` coefficients set to `1` or `-1` at uniformly chosen positions, the rest zero.
+/// Panics if `h > n`.
+pub fn sparse_ternary<T: PolynomialFieldElement, R: RngCore>(
+    n: usize,
+    h: usize,
+    q: T,
+    rng: &mut R,
+) -> Polynomial<T> {
+    let mut coef = vec![0_i64; n];
+    index::sample(rng, n, h)
+        .into_iter()
+        .for_each(|i| coef[i] = if rng.gen::<bool>() { 1 } else { -1 });
+    Polynomial::from_signed(&coef, q)
+}
+
+// The first `n` values below `q` among the `d = bits(q - 1)`-bit chunks of
+// `SHAKE-128(seed || nonce)`, in stream order. Chunks are read least-significant bit first,
+// which for `q = 3329` is exactly the `SampleNTT` parse of ML-KEM.
+fn expand<T: PolynomialFieldElement>(seed: &[u8; 32], nonce: u16, q: T, n: usize) -> Vec<T> {
+    let d = bits(q - T::from(1));
+    let mut xof = Shake128::default();
+    xof.update(seed);
+    xof.update(&nonce.to_le_bytes());
+    let mut reader = xof.finalize_xof();
+
+    // `d` bytes hold exactly eight chunks. They are compared with `q` as words, which `d` cannot
+    // outnumber, so that none wraps around the default modulus on the way in.
+    let top = q.to_words();
+    let mut block = vec![0_u8; d];
+    let mut out = Vec::with_capacity(n);
+    while out.len() < n {
+        reader.read(&mut block);
+        out.extend(
+            (0..8)
+                .map(|i| {
+                    let mut words = chunk(&block, d, i);
+                    words.resize(top.len(), 0);
+                    words
+                })
+                .filter(|w| w.iter().rev().lt(top.iter().rev()))
+                .map(|w| from_words::<T>(&w)),
+        );
+    }
+    out.truncate(n);
+    out
+}
+
+impl<T: PolynomialFieldElement> Polynomial<T> {
+    /// Expands a uniform polynomial mod `q` with `n` coefficients from a public seed. The
+    /// nonce is appended little-endian, so the ML-KEM matrix entry `A[i][j]` uses
+    /// `nonce = j + 256 * i`.
+    pub fn expand_uniform(seed: &[u8; 32], nonce: u16, q: T, n: usize) -> Self {
+        Polynomial::new(expand(seed, nonce, q, n))
+    }
+
+    /// The same stream as `expand_uniform`, taken directly as the NTT-domain values of a
+    /// polynomial mod `c.N`, so no transform is needed. `inverse` recovers the coefficients.
+    pub fn expand_uniform_ntt(seed: &[u8; 32], nonce: u16, c: &Constants<T>, n: usize) -> Vec<T> {
+        expand(seed, nonce, c.N, n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::{
+        error::NttError,
+        ntt::{forward, inverse, working_modulus},
+        numbers::{BigInt256, BigInt512, BigInt64},
+        polynomial::Polynomial,
+        sampling::{
+            centered_binomial, discrete_gaussian, sparse_ternary, ternary, try_discrete_gaussian,
+            uniform,
+        },
+    };
+
+    fn centered(a: &Polynomial<BigInt256>, q: BigInt256) -> Vec<i64> {
+        a.coef
+            .iter()
+            .map(|x| x.to_centered_i64(q).unwrap())
+            .collect_vec()
+    }
+
+    fn mean_var(v: &[i64]) -> (f64, f64) {
+        let n = v.len() as f64;
+        let mean = v.iter().sum::<i64>() as f64 / n;
+        let var = v.iter().map(|&x| (x as f64 - mean).powi(2)).sum::<f64>() / n;
+        (mean, var)
+    }
+
+    #[test]
+    fn test_uniform() {
+        let q = BigInt256::from(3329);
+        let a = uniform(4096, q, &mut StdRng::seed_from_u64(1));
+        assert!(a.coef.iter().all(|&x| x < q));
+        // reproducible from the seed
+        let b = uniform(4096, q, &mut StdRng::seed_from_u64(1));
+        assert_eq!(a.coef, b.coef);
+
+        let v = a
+            .coef
+            .iter()
+            .map(|x| x.to_u32().unwrap() as i64)
+            .collect_vec();
+        let (mean, _) = mean_var(&v);
+        assert!((mean - 1664.0).abs() < 50.0);
+        // both ends of the range are reachable
+        assert!(v.iter().any(|&x| x < 10) && v.iter().any(|&x| x > 3319));
+    }
+
+    #[test]
+    fn test_noise() {
+        let q = BigInt256::from(3329);
+        let mut rng = StdRng::seed_from_u64(7);
+
+        let v = centered(&centered_binomial(4096, 2, q, &mut rng), q);
+        assert!(v.iter().all(|x| x.abs() <= 2));
+        let (mean, var) = mean_var(&v);
+        assert!(mean.abs() < 0.1 && (var - 1.0).abs() < 0.1);
+
+        let v = centered(&discrete_gaussian(4096, 3.2, q, &mut rng), q);
+        let (mean, var) = mean_var(&v);
+        assert!(mean.abs() < 0.3 && (var.sqrt() - 3.2).abs() < 0.2);
+
+        // a zero width would never accept, a negative one has an empty range
+        for sigma in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert_eq!(
+                try_discrete_gaussian(16, sigma, q, &mut rng).unwrap_err(),
+                NttError::InvalidSigma
+            );
+        }
+        assert_eq!(
+            try_discrete_gaussian(16, 1e12, q, &mut rng).unwrap_err(),
+            NttError::Overflow
+        );
+
+        let v = centered(&ternary(4096, q, &mut rng), q);
+        assert!(v.iter().all(|x| x.abs() <= 1));
+        assert!((-1..=1).all(|c| v.iter().filter(|&&x| x == c).count() > 1200));
+
+        let v = centered(&sparse_ternary(256, 64, q, &mut rng), q);
+        assert_eq!(v.iter().filter(|&&x| x != 0).count(), 64);
+        assert!(v.iter().all(|x| x.abs() <= 1));
+    }
+
+    #[test]
+    fn test_expand_uniform() {
+        let seed: [u8; 32] = core::array::from_fn(|i| i as u8);
+
+        // ML-KEM `SampleNTT(seed || 1 || 0)`, checked against an independent implementation
+        let q = BigInt256::from(3329);
+        let a = Polynomial::expand_uniform(&seed, 1, q, 256);
+        let coef = a.coef.iter().map(|x| x.to_u32().unwrap()).collect_vec();
+        assert_eq!(coef[..8], [797, 993, 161, 6, 2608, 2385, 2096, 2661]);
+        assert_eq!(coef[252..], [1419, 79, 5, 2013]);
+        assert_eq!(coef.iter().map(|&x| x as u64).sum::<u64>(), 375544);
+
+        // a 23-bit modulus reads its chunks across byte boundaries
+        let q = BigInt256::from(8380417);
+        let a = Polynomial::expand_uniform(&seed, 5, q, 8);
+        let expected = [
+            7373146, 500408, 769860, 5171133, 7270442, 3277965, 3472079, 803562,
+        ];
+        assert_eq!(a.coef, expected.map(BigInt256::from).to_vec());
+        assert_ne!(
+            Polynomial::expand_uniform(&seed, 6, q, 8).coef,
+            expected.map(BigInt256::from).to_vec()
+        );
+
+        let c = working_modulus(BigInt256::from(16), BigInt256::from(1 << 10));
+        let v = Polynomial::expand_uniform_ntt(&seed, 0, &c, 16);
+        let a = Polynomial::expand_uniform(&seed, 0, c.N, 16);
+        assert_eq!(v, a.coef);
+        assert_eq!(forward(inverse(v.clone(), &c), &c), v);
+
+        // moduli past 256 bits, and one that fills all 64 bits of `BigInt64`
+        let q = (BigInt512::from(1) << 300) + BigInt512::from(1);
+        let a = Polynomial::expand_uniform(&seed, 2, q, 32);
+        assert_eq!(a.coef.len(), 32);
+        assert!(a.coef.iter().all(|&x| x < q));
+        assert!(a.coef.iter().any(|&x| x >= q >> 1));
+        let q = BigInt64::try_from(u64::MAX - 58).unwrap();
+        let a = Polynomial::expand_uniform(&seed, 3, q, 32);
+        assert!(a.coef.iter().all(|&x| x < q));
+        assert!(a.coef.iter().any(|&x| x >= q >> 1));
+    }
+}
@@ -0,0 +1,51 @@
+use rand::Rng;
+
+use crate::{
+    ntt::{working_modulus, Constants},
+    numbers::BigInt256,
+    polynomial::Polynomial,
+};
+
+// 998244353 = 119 * 2^23 + 1, the NTT-friendly prime most tests work modulo
+pub(crate) const P: u64 = 998244353;
+
+// `Z_P` with a root of unity of order `2^20`
+pub(crate) fn constants() -> Constants<BigInt256> {
+    working_modulus(BigInt256::from(1 << 20), BigInt256::from(952))
+}
+
+pub(crate) fn big(v: &[u64]) -> Vec<BigInt256> {
+    v.iter().map(|&x| BigInt256::from(x)).collect()
+}
+
+// coefficients lowest degree first
+pub(crate) fn poly(v: &[u64]) -> Polynomial<BigInt256> {
+    Polynomial::new(big(v))
+}
+
+// coefficients highest degree first
+pub(crate) fn poly_be(v: &[u64]) -> Polynomial<BigInt256> {
+    Polynomial::from_coeffs_be(big(v))
+}
+
+// `n` coefficients below `bound`
+pub(crate) fn random_vec(n: usize, bound: u64, rng: &mut impl Rng) -> Vec<BigInt256> {
+    (0..n)
+        .map(|_| BigInt256::from(rng.gen_range(0..bound)))
+        .collect()
+}
+
+pub(crate) fn random_poly(n: usize, bound: u64, rng: &mut impl Rng) -> Polynomial<BigInt256> {
+    Polynomial::new(random_vec(n, bound, rng))
+}
+
+// `n` coefficients lowest degree first, the last of them 1, so the degree is exactly `n - 1`
+pub(crate) fn random_monic(n: usize, bound: u64, rng: &mut impl Rng) -> Vec<BigInt256> {
+    let mut v = random_vec(n, bound, rng);
+    v[n - 1] = BigInt256::from(1);
+    v
+}
+
+pub(crate) fn reduced(a: Polynomial<BigInt256>, p: BigInt256) -> Polynomial<BigInt256> {
+    Polynomial::new(a.coef.iter().map(|x| x.rem(p)).collect())
+}